@@ -0,0 +1,498 @@
+//! Rule-adaptive basic strategy.
+//! Instead of following a fixed chart, the strategy tables are derived from the actual table rules
+//! and shoe composition by combinatorial analysis of every two-card starting hand against every
+//! dealer up card. The starting cards and the up card are removed from the shoe before the
+//! expected values are calculated, so the tables also reflect the effect of card removal in
+//! games with few decks.
+
+use std::fmt;
+
+use crate::analysis::{add_card, extra_blackjack_loss, Composition, DealerOutcomes, TotalEvaluator, VALUES};
+use crate::basic_strategy::{self, PreferredAction};
+use crate::card::hand::{PlayerHand, PlayerTurn};
+use crate::card::Card;
+use crate::game::HandAction;
+use crate::rules::{PairDefinition, Rules};
+use crate::strategy::{DealerView, Strategy, TableView};
+
+/// The number of rows in the hard table, for hard totals 4 through 21.
+const HARD_ROWS: usize = 18;
+/// The number of rows in the soft table, for soft totals 12 through 21.
+const SOFT_ROWS: usize = 10;
+/// The number of rows in the pair table, for pairs of twos through aces.
+const PAIR_ROWS: usize = 10;
+
+/// The expected values of each action, summed over all starting hands sharing a table entry
+/// and weighted by the probability of being dealt each hand.
+#[derive(Debug, Default, Clone, Copy)]
+struct ActionValues {
+    weight: f64,
+    stand: f64,
+    hit: f64,
+    double: f64,
+    split: f64,
+    /// The expected value of playing the hand without surrendering early,
+    /// before the dealer has checked for blackjack
+    unconditional: f64,
+}
+
+impl ActionValues {
+    fn add(&mut self, weight: f64, other: &Self) {
+        self.weight += weight;
+        self.stand += weight * other.stand;
+        self.hit += weight * other.hit;
+        self.double += weight * other.double;
+        self.split += weight * other.split;
+        self.unconditional += weight * other.unconditional;
+    }
+
+    /// Returns the average expected values of the summed starting hands.
+    fn average(&self) -> Self {
+        Self {
+            weight: 1.0,
+            stand: self.stand / self.weight,
+            hit: self.hit / self.weight,
+            double: self.double / self.weight,
+            split: self.split / self.weight,
+            unconditional: self.unconditional / self.weight,
+        }
+    }
+}
+
+/// Strategy tables for a specific set of rules and number of decks.
+/// Columns are indexed by the dealer's up card, from two through ace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrategyTables {
+    /// The preferred actions for hard totals 4 through 21
+    hard: [[PreferredAction; VALUES]; HARD_ROWS],
+    /// The preferred actions for soft totals 12 through 21
+    soft: [[PreferredAction; VALUES]; SOFT_ROWS],
+    /// The preferred actions for pairs of twos through aces, if splitting is allowed
+    pairs: [[PreferredAction; VALUES]; PAIR_ROWS],
+    /// Whether to surrender early on hard totals 4 through 21
+    early_surrender_hard: [[bool; VALUES]; HARD_ROWS],
+    /// Whether to surrender early on soft totals 12 through 21
+    early_surrender_soft: [[bool; VALUES]; SOFT_ROWS],
+    /// Whether to surrender early on pairs of twos through aces
+    early_surrender_pairs: [[bool; VALUES]; PAIR_ROWS],
+    /// The expected values of playing hard totals 4 through 21 as preferred
+    hard_values: [[f64; VALUES]; HARD_ROWS],
+    /// The expected values of playing soft totals 12 through 21 as preferred
+    soft_values: [[f64; VALUES]; SOFT_ROWS],
+    /// The expected values of playing pairs of twos through aces as preferred
+    pair_values: [[f64; VALUES]; PAIR_ROWS],
+}
+
+impl StrategyTables {
+    /// Derives the strategy tables for the given rules and a full shoe of the given composition,
+    /// e.g. `Composition::full(6)`, or `Composition::from(&shoe)` for a shoe with cards removed.
+    #[must_use]
+    pub fn generate(rules: &Rules, shoe: &Composition) -> Self {
+        let mut tables = Self {
+            hard: [[PreferredAction::Stand; VALUES]; HARD_ROWS],
+            soft: [[PreferredAction::Stand; VALUES]; SOFT_ROWS],
+            pairs: [[PreferredAction::Stand; VALUES]; PAIR_ROWS],
+            early_surrender_hard: [[false; VALUES]; HARD_ROWS],
+            early_surrender_soft: [[false; VALUES]; SOFT_ROWS],
+            early_surrender_pairs: [[false; VALUES]; PAIR_ROWS],
+            hard_values: [[0.0; VALUES]; HARD_ROWS],
+            soft_values: [[0.0; VALUES]; SOFT_ROWS],
+            pair_values: [[0.0; VALUES]; PAIR_ROWS],
+        };
+        for up_card in 2..=11 {
+            let column = usize::from(up_card - 2);
+            let mut shoe = *shoe;
+            shoe.remove(up_card);
+            let mut hard = [ActionValues::default(); HARD_ROWS];
+            let mut soft = [ActionValues::default(); SOFT_ROWS];
+            let mut pairs = [ActionValues::default(); PAIR_ROWS];
+            for first in 2..=11 {
+                for second in first..=11 {
+                    let (total, is_soft) = add_card(0, false, first);
+                    let (total, is_soft) = add_card(total, is_soft, second);
+                    if is_soft && total == 21 {
+                        continue; // Blackjack, there is nothing to decide
+                    }
+                    let mut weight = f64::from(shoe.count(first));
+                    let mut composition = shoe;
+                    composition.remove(first);
+                    weight *= f64::from(composition.count(second));
+                    if weight == 0.0 {
+                        continue;
+                    }
+                    composition.remove(second);
+                    if first != second {
+                        weight *= 2.0; // The two cards can be dealt in either order
+                    }
+                    let values = evaluate(rules, &composition, up_card, total, is_soft, first == second);
+                    if is_soft {
+                        soft[usize::from(total - 12)].add(weight, &values);
+                    } else {
+                        hard[usize::from(total - 4)].add(weight, &values);
+                    }
+                    if first == second {
+                        pairs[usize::from(first - 2)].add(weight, &values);
+                    }
+                }
+            }
+            // Some totals cannot be made with two cards, so they are evaluated with a full shoe
+            for (row, values) in hard.iter_mut().enumerate() {
+                if values.weight == 0.0 {
+                    values.add(1.0, &evaluate(rules, &shoe, up_card, row as u8 + 4, false, false));
+                }
+            }
+            for (row, values) in soft.iter_mut().enumerate() {
+                if values.weight == 0.0 {
+                    values.add(1.0, &evaluate(rules, &shoe, up_card, row as u8 + 12, true, false));
+                }
+            }
+            for (row, values) in hard.iter().enumerate() {
+                let values = values.average();
+                tables.hard[row][column] = choose(rules, &values, false);
+                tables.early_surrender_hard[row][column] = surrender_early(rules, &values);
+                tables.hard_values[row][column] = best_value(rules, &values, false);
+            }
+            for (row, values) in soft.iter().enumerate() {
+                let values = values.average();
+                tables.soft[row][column] = choose(rules, &values, false);
+                tables.early_surrender_soft[row][column] = surrender_early(rules, &values);
+                tables.soft_values[row][column] = best_value(rules, &values, false);
+            }
+            for (row, values) in pairs.iter().enumerate() {
+                let values = values.average();
+                let split_allowed = rules.max_splits_of_value(row as u8 + 2) != Some(0)
+                    && (row != PAIR_ROWS - 1 || rules.split_aces);
+                tables.pairs[row][column] = choose(rules, &values, split_allowed);
+                tables.early_surrender_pairs[row][column] = surrender_early(rules, &values);
+                tables.pair_values[row][column] = best_value(rules, &values, split_allowed);
+            }
+        }
+        tables
+    }
+
+    /// Returns the preferred action for the current hand of the player's turn.
+    #[must_use]
+    pub fn preferred_action(&self, table: TableView, player_turn: &PlayerTurn, dealer_hand: DealerView) -> PreferredAction {
+        let hand = player_turn.current_hand();
+        let column = usize::from(dealer_hand.showing() - 2);
+        if table.check_split_allowed(player_turn).is_ok() {
+            self.pairs[usize::from(hand.cards[0].rank.worth() - 2)][column]
+        } else if hand.value.soft {
+            self.soft[usize::from(hand.value.total - 12)][column]
+        } else {
+            self.hard[usize::from(hand.value.total - 4)][column]
+        }
+    }
+
+    /// Returns the expected value of a starting hand of the two given cards against the dealer's up
+    /// card, when played as preferred and given that the dealer does not have blackjack.
+    /// This is what decides whether to switch cards in Blackjack Switch.
+    #[must_use]
+    pub fn starting_hand_value(&self, rules: &Rules, cards: [&Card; 2], up_card: u8) -> f64 {
+        let column = usize::from(up_card - 2);
+        let (first, second) = (cards[0].rank.worth(), cards[1].rank.worth());
+        let (total, soft) = add_card(0, false, first);
+        let (total, soft) = add_card(total, soft, second);
+        let pair = match rules.pair_definition {
+            PairDefinition::SameRank => cards[0].rank == cards[1].rank,
+            PairDefinition::SameValue => first == second,
+        };
+        if soft && total == 21 {
+            let (numerator, denominator) = rules.blackjack_payout.ratio();
+            f64::from(numerator) / f64::from(denominator)
+        } else if pair {
+            self.pair_values[usize::from(first - 2)][column]
+        } else if soft {
+            self.soft_values[usize::from(total - 12)][column]
+        } else {
+            self.hard_values[usize::from(total - 4)][column]
+        }
+    }
+
+    /// Returns whether to surrender the player's starting hand early, which is a pair by the
+    /// pair definition of the given rules.
+    #[must_use]
+    pub fn surrender_early(&self, rules: &Rules, player_hand: &PlayerHand, dealer_hand: DealerView) -> bool {
+        let column = usize::from(dealer_hand.showing() - 2);
+        if player_hand.is_pair_by(rules.pair_definition) {
+            self.early_surrender_pairs[usize::from(player_hand.cards[0].rank.worth() - 2)][column]
+        } else if player_hand.value.soft {
+            self.early_surrender_soft[usize::from(player_hand.value.total - 12)][column]
+        } else {
+            self.early_surrender_hard[usize::from(player_hand.value.total - 4)][column]
+        }
+    }
+}
+
+/// Calculates the expected values of each action for a starting hand with the given total,
+/// drawing from the given shoe against the given dealer up card.
+fn evaluate(rules: &Rules, composition: &Composition, up_card: u8, total: u8, soft: bool, pair: bool) -> ActionValues {
+    let dealer = DealerOutcomes::calculate(composition, up_card, rules);
+    // Hands are valued given that the dealer does not have blackjack, which is all that matters
+    // after a peek. Without a peek, doubling and splitting also risk more against a blackjack.
+    let evaluator = TotalEvaluator::new(composition.probabilities(), &dealer.without_blackjack(), rules);
+    let extra_loss = extra_blackjack_loss(rules, up_card, &dealer);
+    let mut values = ActionValues {
+        weight: 1.0,
+        stand: evaluator.stand(total),
+        hit: evaluator.hit(total, soft),
+        double: if rules.double_restriction.allows(2, total, soft) {
+            evaluator.double_two_cards(total, soft) - extra_loss
+        } else {
+            f64::NEG_INFINITY
+        },
+        split: if pair {
+            evaluator.split(if soft { 11 } else { total / 2 }, rules) - extra_loss
+        } else {
+            f64::NEG_INFINITY
+        },
+        unconditional: 0.0,
+    };
+    let mut best = values.stand.max(values.hit).max(values.double).max(values.split);
+    if rules.late_surrender {
+        best = best.max(-0.5);
+    }
+    values.unconditional = -dealer.blackjack + (1.0 - dealer.blackjack) * best;
+    values
+}
+
+/// Chooses the preferred action with the highest expected value.
+fn choose(rules: &Rules, values: &ActionValues, split_allowed: bool) -> PreferredAction {
+    let play = if values.hit > values.stand {
+        PreferredAction::Hit
+    } else {
+        PreferredAction::Stand
+    };
+    let play_value = values.hit.max(values.stand);
+    let (best, best_value) = if split_allowed && values.split > play_value.max(values.double) {
+        (PreferredAction::Split, values.split)
+    } else if values.double > play_value {
+        let double = if play == PreferredAction::Hit {
+            PreferredAction::DoubleOrHit
+        } else {
+            PreferredAction::DoubleOrStand
+        };
+        (double, values.double)
+    } else {
+        (play, play_value)
+    };
+    if !rules.late_surrender || best_value >= -0.5 {
+        return best;
+    }
+    match best {
+        PreferredAction::Split => PreferredAction::SurrenderOrSplit,
+        _ if play == PreferredAction::Hit => PreferredAction::SurrenderOrHit,
+        _ => PreferredAction::SurrenderOrStand,
+    }
+}
+
+/// Returns the expected value of the preferred action.
+fn best_value(rules: &Rules, values: &ActionValues, split_allowed: bool) -> f64 {
+    let mut best = values.stand.max(values.hit).max(values.double);
+    if split_allowed {
+        best = best.max(values.split);
+    }
+    if rules.late_surrender {
+        best = best.max(-0.5);
+    }
+    best
+}
+
+/// Returns whether surrendering early is better than playing on.
+fn surrender_early(rules: &Rules, values: &ActionValues) -> bool {
+    rules.early_surrender && values.unconditional < -0.5
+}
+
+impl fmt::Display for StrategyTables {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn symbol(action: PreferredAction) -> &'static str {
+            match action {
+                PreferredAction::Stand => "S",
+                PreferredAction::Hit => "H",
+                PreferredAction::Split => "P",
+                PreferredAction::DoubleOrHit => "Dh",
+                PreferredAction::DoubleOrStand => "Ds",
+                PreferredAction::SurrenderOrHit => "Rh",
+                PreferredAction::SurrenderOrStand => "Rs",
+                PreferredAction::SurrenderOrSplit => "Rp",
+                PreferredAction::SplitIfDoubleAfterSplitAllowedElseHit => "Ph",
+            }
+        }
+        fn row(f: &mut fmt::Formatter<'_>, label: &str, actions: &[PreferredAction; VALUES]) -> fmt::Result {
+            write!(f, "{label:>5} ")?;
+            for action in actions {
+                write!(f, "{:>3}", symbol(*action))?;
+            }
+            writeln!(f)
+        }
+
+        writeln!(f, "        2  3  4  5  6  7  8  9 10  A")?;
+        for (i, actions) in self.hard.iter().enumerate() {
+            row(f, &format!("H{}", i + 4), actions)?;
+        }
+        for (i, actions) in self.soft.iter().enumerate() {
+            row(f, &format!("S{}", i + 12), actions)?;
+        }
+        for (i, actions) in self.pairs.iter().enumerate() {
+            let card = if i == PAIR_ROWS - 1 { "A".to_string() } else { (i + 2).to_string() };
+            row(f, &format!("{card},{card}"), actions)?;
+        }
+        Ok(())
+    }
+}
+
+/// A player who follows basic strategy derived from the table's actual rules and always bets the
+/// same amount.
+#[derive(Debug, Clone)]
+pub struct AdaptiveStrategy {
+    tables: StrategyTables,
+}
+
+impl AdaptiveStrategy {
+    /// Creates a strategy for the given rules and a full shoe of the given composition.
+    /// This derives the strategy tables, which takes a moment.
+    #[must_use]
+    pub fn new(rules: &Rules, shoe: &Composition) -> Self {
+        Self {
+            tables: StrategyTables::generate(rules, shoe),
+        }
+    }
+
+    /// The strategy tables this player follows.
+    #[must_use]
+    pub const fn tables(&self) -> &StrategyTables {
+        &self.tables
+    }
+}
+
+impl Strategy for AdaptiveStrategy {
+    fn bet(&mut self, _table: TableView) -> u32 {
+        basic_strategy::bet()
+    }
+
+    fn surrender_early(
+        &mut self,
+        table: TableView,
+        player_hand: &PlayerHand,
+        dealer_hand: DealerView,
+    ) -> bool {
+        self.tables.surrender_early(table.rules(), player_hand, dealer_hand)
+    }
+
+    fn bet_insurance(
+        &mut self,
+        _table: TableView,
+        _player_hand: &PlayerHand,
+        _dealer_hand: DealerView,
+    ) -> u32 {
+        basic_strategy::bet_insurance()
+    }
+
+    /// Switches if the two hands after switching are worth more than the two hands before.
+    fn switch_cards(
+        &mut self,
+        table: TableView,
+        player_hands: &[PlayerHand],
+        dealer_hand: DealerView,
+    ) -> bool {
+        let [first, second] = player_hands else {
+            return false;
+        };
+        let value = |one: &Card, other: &Card| {
+            self.tables.starting_hand_value(table.rules(), [one, other], dealer_hand.showing())
+        };
+        let keep = value(&first.cards[0], &first.cards[1]) + value(&second.cards[0], &second.cards[1]);
+        let switch = value(&first.cards[0], &second.cards[1]) + value(&second.cards[0], &first.cards[1]);
+        switch > keep
+    }
+
+    fn play_hand(
+        &mut self,
+        table: TableView,
+        player_turn: &PlayerTurn,
+        dealer_hand: DealerView,
+    ) -> HandAction {
+        let preferred = self.tables.preferred_action(table, player_turn, dealer_hand);
+        basic_strategy::resolve(table, player_turn, preferred)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::shoe::Shoe;
+    use crate::card::Rank;
+    use crate::game::Table;
+    use crate::rules::DealerSoft17Action;
+    use crate::strategy::{DealerView, TableView};
+    use crate::test_helpers::{card, dealer, hand_of};
+
+    #[test]
+    fn test_generated_tables_match_published_strategy() {
+        let rules = Rules::default();
+        let tables = StrategyTables::generate(&rules, &Composition::full(6));
+        let hard = |total: usize, up: usize| tables.hard[total - 4][up - 2];
+        let soft = |total: usize, up: usize| tables.soft[total - 12][up - 2];
+        let pair = |card: usize, up: usize| tables.pairs[card - 2][up - 2];
+        assert_eq!(hard(8, 6), PreferredAction::Hit);
+        assert_eq!(hard(11, 6), PreferredAction::DoubleOrHit);
+        assert_eq!(hard(12, 2), PreferredAction::Hit);
+        assert_eq!(hard(12, 4), PreferredAction::Stand);
+        assert_eq!(hard(16, 10), PreferredAction::SurrenderOrHit);
+        assert_eq!(hard(17, 10), PreferredAction::Stand);
+        assert_eq!(soft(18, 6), PreferredAction::DoubleOrStand);
+        assert_eq!(soft(18, 9), PreferredAction::Hit);
+        assert_eq!(pair(8, 10), PreferredAction::Split);
+        assert_eq!(pair(11, 11), PreferredAction::Split);
+        assert_eq!(pair(10, 6), PreferredAction::Stand);
+        assert_eq!(pair(5, 6), PreferredAction::DoubleOrHit);
+    }
+
+    #[test]
+    fn test_generated_tables_adapt_to_rules() {
+        let stand = StrategyTables::generate(&Rules::default(), &Composition::full(6));
+        let hit = StrategyTables::generate(
+            &Rules {
+                dealer_soft_17: DealerSoft17Action::Hit,
+                ..Rules::default()
+            },
+            &Composition::full(6),
+        );
+        // Against a dealer hitting soft 17, hard 11 should be doubled against an ace
+        assert_eq!(stand.hard[11 - 4][11 - 2], PreferredAction::Hit);
+        assert_eq!(hit.hard[11 - 4][11 - 2], PreferredAction::DoubleOrHit);
+        // Free doubles and splits are always taken, but never on tens
+        let free_bet = StrategyTables::generate(&Rules::free_bet(), &Composition::full(6));
+        assert_eq!(free_bet.hard[9 - 4][11 - 2], PreferredAction::DoubleOrHit);
+        assert_eq!(free_bet.pairs[9 - 2][7 - 2], PreferredAction::Split);
+        assert_eq!(free_bet.pairs[10 - 2][6 - 2], PreferredAction::Stand);
+    }
+
+    #[test]
+    fn test_generated_tables_adapt_to_shoe() {
+        let rules = Rules::default();
+        let full = StrategyTables::generate(&rules, &Composition::full(6));
+        let shoe = Shoe::new(6, 0.75).without_rank(&Rank::Ten);
+        let spanish = StrategyTables::generate(&rules, &Composition::from(&shoe));
+        // Without the tens, stiff hands bust less often and doubles draw fewer tens
+        assert_eq!(full.hard[13 - 4][2 - 2], PreferredAction::Stand);
+        assert_eq!(spanish.hard[13 - 4][2 - 2], PreferredAction::Hit);
+        assert_eq!(full.hard[11 - 4][10 - 2], PreferredAction::DoubleOrHit);
+        assert_eq!(spanish.hard[11 - 4][10 - 2], PreferredAction::Hit);
+    }
+
+    #[test]
+    fn test_switch_cards() {
+        let rules = Rules::blackjack_switch();
+        let mut strategy = AdaptiveStrategy::new(&rules, &Composition::full(6));
+        let table = Table::new(1_000, Shoe::new(6, 0.75), rules);
+        let table = TableView::new(&table);
+        let pair_of = |first, second| hand_of([first, second].map(card), 100);
+        let dealer_hand = dealer([card(Rank::Ten)]);
+        // A 15 and a 16 are switched into a 20 and an 11, which are kept
+        let stiffs = [pair_of(Rank::Ten, Rank::Five), pair_of(Rank::Six, Rank::King)];
+        assert!(strategy.switch_cards(table, &stiffs, DealerView::new(&dealer_hand)));
+        let switched = [pair_of(Rank::Ten, Rank::King), pair_of(Rank::Six, Rank::Five)];
+        assert!(!strategy.switch_cards(table, &switched, DealerView::new(&dealer_hand)));
+    }
+}
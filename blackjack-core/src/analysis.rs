@@ -0,0 +1,1028 @@
+//! Combinatorial analysis of blackjack hands.
+//! Instead of simulating, this module calculates expected values by enumerating the cards
+//! which could be drawn from the shoe, weighted by their probability.
+//! All expected values are expressed in units of the initial bet.
+
+use std::collections::HashMap;
+
+use crate::card::hand::PlayerHand;
+use crate::card::shoe::Shoe;
+use crate::game::HandAction;
+use crate::rules::{DealerSoft17Action, DoubleRestriction, PairDefinition, Rules};
+
+/// The number of distinct card values: two through ten, and ace.
+pub const VALUES: usize = 10;
+
+/// Returns the index of a card value (2-11) in the arrays of this module.
+const fn index(value: u8) -> usize {
+    value as usize - 2
+}
+
+/// Returns the card value (2-11) at the given index in the arrays of this module.
+const fn value(index: usize) -> u8 {
+    index as u8 + 2
+}
+
+/// Adds a card value to a hand total, following the same rules as the hand value in the game:
+/// an ace is worth 11 unless that would bust the hand, and a soft ace is converted to a hard ace
+/// before busting.
+#[must_use]
+pub const fn add_card(total: u8, soft: bool, card: u8) -> (u8, bool) {
+    let (mut worth, mut card_soft) = (card, card == 11);
+    if card_soft && total + worth > 21 {
+        worth -= 10;
+        card_soft = false;
+    }
+    let (mut total, mut soft) = (total, soft);
+    if soft && total + worth > 21 {
+        total -= 10;
+        soft = false;
+    }
+    (total + worth, soft || card_soft)
+}
+
+/// The number of cards of each value remaining in the shoe.
+/// Ten-valued cards (tens and face cards) are counted together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Composition {
+    counts: [u16; VALUES],
+}
+
+impl Composition {
+    /// The composition of a full shoe with the given number of decks.
+    #[must_use]
+    pub fn full(decks: u8) -> Self {
+        let decks = u16::from(decks);
+        let mut counts = [4 * decks; VALUES];
+        counts[index(10)] = 16 * decks;
+        Self { counts }
+    }
+
+    /// Returns the number of cards with the given value (2-11) in the shoe.
+    #[must_use]
+    pub const fn count(&self, value: u8) -> u16 {
+        self.counts[index(value)]
+    }
+
+    /// Returns the total number of cards in the shoe.
+    #[must_use]
+    pub fn total(&self) -> u16 {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the probability of drawing a card with the given value (2-11) from the shoe.
+    #[must_use]
+    pub fn probability(&self, value: u8) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => f64::from(self.count(value)) / f64::from(total),
+        }
+    }
+
+    /// Removes a card with the given value (2-11) from the shoe.
+    /// # Panics
+    /// Panics if there is no such card left in the shoe.
+    pub fn remove(&mut self, value: u8) {
+        let count = &mut self.counts[index(value)];
+        *count = count.checked_sub(1).expect("card must be in the shoe");
+    }
+
+    /// Returns a card with the given value (2-11) to the shoe.
+    pub fn add(&mut self, value: u8) {
+        self.counts[index(value)] += 1;
+    }
+
+    /// Returns the probabilities of drawing each card value, indexed by value - 2.
+    #[must_use]
+    pub fn probabilities(&self) -> [f64; VALUES] {
+        let mut probabilities = [0.0; VALUES];
+        for (i, probability) in probabilities.iter_mut().enumerate() {
+            *probability = self.probability(value(i));
+        }
+        probabilities
+    }
+}
+
+impl From<&Shoe> for Composition {
+    /// The composition of the cards left in the given shoe.
+    fn from(shoe: &Shoe) -> Self {
+        let mut counts = [0; VALUES];
+        for (card, count) in shoe.remaining_cards() {
+            counts[index(card.rank.worth())] += count;
+        }
+        Self { counts }
+    }
+}
+
+/// The probabilities of each final result of the dealer's hand.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct DealerOutcomes {
+    /// The probability of the dealer standing on 17 through 21, indexed by total - 17
+    pub totals: [f64; 5],
+    /// The probability of the dealer busting
+    pub bust: f64,
+    /// The probability of the dealer busting with 22 if that pushes, which is not included in bust
+    pub push_22: f64,
+    /// The probability of the dealer having blackjack
+    pub blackjack: f64,
+}
+
+impl DealerOutcomes {
+    /// Calculates the probabilities of the dealer's final hand given their up card and the cards
+    /// left in the shoe, which must not include the up card, under the given rules.
+    /// Every card the dealer draws is removed from the shoe before the next one is drawn.
+    #[must_use]
+    pub fn calculate(composition: &Composition, up_card: u8, rules: &Rules) -> Self {
+        let mut calculation = DealerCalculation {
+            composition: *composition,
+            removed: [0; VALUES],
+            hits_soft_17: rules.dealer_soft_17 == DealerSoft17Action::Hit,
+            pushes_22: rules.dealer_22_pushes,
+            memo: HashMap::new(),
+        };
+        let (total, soft) = add_card(0, false, up_card);
+        calculation.draw(total, soft, 1)
+    }
+
+    /// Returns these outcomes conditioned on the dealer not having blackjack,
+    /// which is what the player faces after the dealer has peeked at their hole card.
+    #[must_use]
+    pub fn without_blackjack(&self) -> Self {
+        let remaining = 1.0 - self.blackjack;
+        if remaining <= 0.0 {
+            return Self::default();
+        }
+        let mut totals = self.totals;
+        totals.iter_mut().for_each(|total| *total /= remaining);
+        Self {
+            totals,
+            bust: self.bust / remaining,
+            push_22: self.push_22 / remaining,
+            blackjack: 0.0,
+        }
+    }
+
+    /// Returns the probability of winning by standing on the given total (excluding player
+    /// blackjack) against these outcomes.
+    #[must_use]
+    pub fn win_probability(&self, total: u8) -> f64 {
+        if total > 21 {
+            return 0.0;
+        }
+        let beaten: f64 = self.totals.iter().take(usize::from(total.saturating_sub(17))).sum();
+        self.bust + beaten
+    }
+
+    /// Returns the expected value of standing on the given total (excluding player blackjack)
+    /// against these outcomes. A dealer blackjack counts as a loss, and a dealer 22 which pushes
+    /// counts as neither a win nor a loss.
+    #[must_use]
+    pub fn stand_ev(&self, total: u8) -> f64 {
+        if total > 21 {
+            return -1.0;
+        }
+        let mut ev = self.bust - self.blackjack;
+        for (i, probability) in self.totals.iter().enumerate() {
+            let dealer_total = 17 + i as u8;
+            ev += match total.cmp(&dealer_total) {
+                std::cmp::Ordering::Greater => *probability,
+                std::cmp::Ordering::Equal => 0.0,
+                std::cmp::Ordering::Less => -*probability,
+            };
+        }
+        ev
+    }
+
+    fn add_scaled(&mut self, other: &Self, scale: f64) {
+        for (total, other) in self.totals.iter_mut().zip(&other.totals) {
+            *total += other * scale;
+        }
+        self.bust += other.bust * scale;
+        self.push_22 += other.push_22 * scale;
+        self.blackjack += other.blackjack * scale;
+    }
+}
+
+/// The state of a calculation of the dealer's outcomes.
+/// Different orders of drawing the same cards lead to the same state,
+/// so the outcomes of each state are remembered by the multiset of cards drawn so far.
+struct DealerCalculation {
+    composition: Composition,
+    removed: [u8; VALUES],
+    hits_soft_17: bool,
+    pushes_22: bool,
+    memo: HashMap<[u8; VALUES], DealerOutcomes>,
+}
+
+impl DealerCalculation {
+    /// Calculates the outcomes of a dealer hand with the given total and number of cards.
+    fn draw(&mut self, total: u8, soft: bool, cards: u8) -> DealerOutcomes {
+        let mut outcomes = DealerOutcomes::default();
+        match (soft, total) {
+            (true, 21) if cards == 2 => outcomes.blackjack = 1.0,
+            (_, 22) if self.pushes_22 => outcomes.push_22 = 1.0,
+            (_, 22..) => outcomes.bust = 1.0,
+            (true, 17) if self.hits_soft_17 => return self.draw_next(total, soft, cards),
+            (_, 17..=21) => outcomes.totals[usize::from(total - 17)] = 1.0,
+            _ => return self.draw_next(total, soft, cards),
+        }
+        outcomes
+    }
+
+    /// Calculates the outcomes of a dealer hand which must draw another card.
+    fn draw_next(&mut self, total: u8, soft: bool, cards: u8) -> DealerOutcomes {
+        if let Some(outcomes) = self.memo.get(&self.removed) {
+            return *outcomes;
+        }
+        let mut outcomes = DealerOutcomes::default();
+        let remaining = f64::from(self.composition.total());
+        for i in 0..VALUES {
+            let count = self.composition.counts[i];
+            if count == 0 {
+                continue;
+            }
+            let card = value(i);
+            self.composition.remove(card);
+            self.removed[i] += 1;
+            let (next_total, next_soft) = add_card(total, soft, card);
+            let next = self.draw(next_total, next_soft, cards + 1);
+            self.removed[i] -= 1;
+            self.composition.add(card);
+            outcomes.add_scaled(&next, f64::from(count) / remaining);
+        }
+        self.memo.insert(self.removed, outcomes);
+        outcomes
+    }
+}
+
+/// The expected value of playing a hand, along with its probability of losing.
+/// A free bet only costs the house when the hand loses.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Outcome {
+    ev: f64,
+    loss: f64,
+}
+
+impl Outcome {
+    /// A busted hand, which is always lost.
+    const BUST: Self = Self { ev: -1.0, loss: 1.0 };
+
+    /// Returns the outcome with the higher expected value.
+    fn max(self, other: Self) -> Self {
+        if other.ev > self.ev {
+            other
+        } else {
+            self
+        }
+    }
+
+    /// Removes an outcome with the given probability from a weighted sum of outcomes.
+    fn without(self, probability: f64, outcome: Self) -> Self {
+        Self {
+            ev: self.ev - probability * outcome.ev,
+            loss: self.loss - probability * outcome.loss,
+        }
+    }
+}
+
+/// The expected values of the player's options for a hand of a given total.
+/// The player's cards are drawn from a fixed distribution, so these values only depend on the
+/// hand total and whether it is soft, not on the exact cards in the hand.
+#[derive(Debug, Clone)]
+pub struct TotalEvaluator {
+    /// The probabilities of drawing each card value, indexed by value - 2
+    probabilities: [f64; VALUES],
+    /// The expected value of standing on each total from 0 to 21
+    stand: [f64; 22],
+    /// The probability of winning by standing on each total from 0 to 21
+    win: [f64; 22],
+    /// The outcome of playing on optimally (hit or stand) from each hard total from 0 to 21
+    best_hard: [Outcome; 22],
+    /// The outcome of playing on optimally (hit or stand) from each soft total from 0 to 21
+    best_soft: [Outcome; 22],
+    /// Whether the player may double down after hitting
+    double_after_hitting: bool,
+    /// Whether doubling down on a hard 9, 10 or 11 with two cards is free
+    free_doubles: bool,
+}
+
+impl TotalEvaluator {
+    /// Creates an evaluator for a player drawing from the given card probabilities,
+    /// against the given dealer outcomes.
+    /// If the rules allow doubling down on any number of cards, playing on includes doubling
+    /// after hitting, even on split hands.
+    #[must_use]
+    pub fn new(probabilities: [f64; VALUES], dealer: &DealerOutcomes, rules: &Rules) -> Self {
+        let mut stand = [0.0; 22];
+        let mut win = [0.0; 22];
+        for total in 0..22 {
+            stand[total] = dealer.stand_ev(total as u8);
+            win[total] = dealer.win_probability(total as u8);
+        }
+        let mut evaluator = Self {
+            probabilities,
+            stand,
+            win,
+            best_hard: [Outcome::default(); 22],
+            best_soft: [Outcome::default(); 22],
+            double_after_hitting: rules.double_restriction == DoubleRestriction::AnyCards,
+            free_doubles: rules.free_doubles,
+        };
+        // Hard hands of 11 or more only ever become higher hard hands, so they are solved first,
+        // from the top down. Soft hands become higher soft hands or hard hands of 12 or more,
+        // and lower hard hands can become any of these, e.g. a hard 10 and an ace make a soft 21.
+        for total in (11..=21).rev() {
+            evaluator.best_hard[total] = evaluator.play_on(total as u8, false);
+        }
+        for total in (12..=21).rev() {
+            evaluator.best_soft[total] = evaluator.play_on(total as u8, true);
+        }
+        for total in (0..=10).rev() {
+            evaluator.best_hard[total] = evaluator.play_on(total as u8, false);
+        }
+        evaluator
+    }
+
+    /// Calculates the outcome of the best option after hitting, from the outcomes of
+    /// the higher totals which have already been solved.
+    fn play_on(&self, total: u8, soft: bool) -> Outcome {
+        let outcome = self.stand_outcome(total).max(self.hit_outcome(total, soft));
+        if self.double_after_hitting {
+            outcome.max(self.double_outcome(total, soft))
+        } else {
+            outcome
+        }
+    }
+
+    /// The expected value of standing.
+    #[must_use]
+    pub fn stand(&self, total: u8) -> f64 {
+        self.stand[total as usize]
+    }
+
+    fn stand_outcome(&self, total: u8) -> Outcome {
+        let (ev, win) = (self.stand[total as usize], self.win[total as usize]);
+        Outcome { ev, loss: win - ev }
+    }
+
+    /// The expected value of hitting once and then playing on optimally.
+    #[must_use]
+    pub fn hit(&self, total: u8, soft: bool) -> f64 {
+        self.hit_outcome(total, soft).ev
+    }
+
+    fn hit_outcome(&self, total: u8, soft: bool) -> Outcome {
+        self.draw(total, soft, |total, soft| self.best_outcome(total, soft))
+    }
+
+    /// The expected value of doubling down, i.e. drawing exactly one more card for twice the bet.
+    #[must_use]
+    pub fn double(&self, total: u8, soft: bool) -> f64 {
+        self.double_outcome(total, soft).ev
+    }
+
+    fn double_outcome(&self, total: u8, soft: bool) -> Outcome {
+        let outcome = self.draw(total, soft, |total, _| self.stand_outcome(total));
+        Outcome { ev: 2.0 * outcome.ev, ..outcome }
+    }
+
+    /// The expected value of doubling down on a hand of two cards, which is free on a hard 9, 10
+    /// or 11 if the rules offer free doubles. A free double only loses the original bet, but wins
+    /// twice the bet.
+    #[must_use]
+    pub fn double_two_cards(&self, total: u8, soft: bool) -> f64 {
+        self.double_two_cards_outcome(total, soft).ev
+    }
+
+    fn double_two_cards_outcome(&self, total: u8, soft: bool) -> Outcome {
+        if self.free_doubles && DoubleRestriction::NineToEleven.allows(2, total, soft) {
+            self.draw(total, soft, |total, _| {
+                let outcome = self.stand_outcome(total);
+                Outcome { ev: outcome.ev + self.win[total as usize], ..outcome }
+            })
+        } else {
+            self.double_outcome(total, soft)
+        }
+    }
+
+    /// The expected value of playing on optimally from the given total, either standing or hitting,
+    /// or doubling down if the rules allow doubling after hitting.
+    #[must_use]
+    pub fn best(&self, total: u8, soft: bool) -> f64 {
+        self.best_outcome(total, soft).ev
+    }
+
+    fn best_outcome(&self, total: u8, soft: bool) -> Outcome {
+        if soft {
+            self.best_soft[total as usize]
+        } else {
+            self.best_hard[total as usize]
+        }
+    }
+
+    /// The expected value of splitting a pair of cards with the given value into two hands.
+    /// Each split hand receives a second card and is then played on optimally, hitting and doubling
+    /// if the rules allow it, or resplit up to the maximum number of splits, see `split_value`.
+    #[must_use]
+    pub fn split(&self, card: u8, rules: &Rules) -> f64 {
+        let (can_hit, can_double) = split_hand_options(rules, card);
+        let (total, soft) = add_card(0, false, card);
+        let play_on = |total, soft| {
+            // The dealer does not have blackjack, so a split hand counted as blackjack always wins
+            if rules.split_21_is_blackjack && soft && total == 21 {
+                return Outcome { ev: blackjack_payout(rules), loss: 0.0 };
+            }
+            let outcome = if can_hit {
+                self.best_outcome(total, soft)
+            } else {
+                self.stand_outcome(total)
+            };
+            if can_double && rules.double_restriction.allows(2, total, soft) {
+                outcome.max(self.double_two_cards_outcome(total, soft))
+            } else {
+                outcome
+            }
+        };
+        let hand = self.draw(total, soft, play_on);
+        let (pair_total, pair_soft) = add_card(total, soft, card);
+        let pair = play_on(pair_total, pair_soft);
+        let resplit = resplit_probability(rules, card, self.probabilities[index(card)]);
+        split_value(rules, card, hand.without(resplit, pair), resplit, pair)
+    }
+
+    /// Draws a card onto the given hand and returns the outcome of the resulting hand,
+    /// where busted hands are lost and other hands are evaluated with the given function.
+    fn draw(&self, total: u8, soft: bool, evaluate: impl Fn(u8, bool) -> Outcome) -> Outcome {
+        let mut outcome = Outcome::default();
+        for (i, probability) in self.probabilities.iter().enumerate() {
+            let (total, soft) = add_card(total, soft, value(i));
+            let next = if total > 21 { Outcome::BUST } else { evaluate(total, soft) };
+            outcome.ev += probability * next.ev;
+            outcome.loss += probability * next.loss;
+        }
+        outcome
+    }
+}
+
+/// The expected values of the player's options for a specific hand.
+/// Options which are not available for the hand are None.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HandAnalysis {
+    pub stand: f64,
+    pub hit: f64,
+    pub double: Option<f64>,
+    pub split: Option<f64>,
+    pub surrender: Option<f64>,
+}
+
+impl HandAnalysis {
+    /// Returns the option with the highest expected value, along with that expected value.
+    #[must_use]
+    pub fn best(&self) -> (HandAction, f64) {
+        [
+            (HandAction::Stand, Some(self.stand)),
+            (HandAction::Hit, Some(self.hit)),
+            (HandAction::Double, self.double),
+            (HandAction::Split, self.split),
+            (HandAction::Surrender, self.surrender),
+        ]
+        .into_iter()
+        .filter_map(|(action, ev)| ev.map(|ev| (action, ev)))
+        .fold((HandAction::Stand, f64::NEG_INFINITY), |best, (action, ev)| {
+            if ev > best.1 {
+                (action, ev)
+            } else {
+                best
+            }
+        })
+    }
+}
+
+/// Calculates the expected values of the player's options for their hand against the dealer's up
+/// card, given the cards left in the shoe (which must not include the player's cards or the up
+/// card).
+///
+/// Every card the player and the dealer could draw is enumerated and removed from the shoe before
+/// the next one is drawn. If the dealer peeks for blackjack with the up card, the expected values
+/// are conditioned on the dealer not having blackjack. Otherwise, including without a hole card,
+/// the expected values include the chance of losing to a dealer blackjack.
+///
+/// Standing, hitting and doubling are exact, but splitting is approximated:
+/// - Both split hands, and any resplit hands, draw from the same shoe, as if the other split hands
+///   did not remove any cards.
+/// - A dealer blackjack is assumed to cost exactly both split bets, so doubles after splitting and
+///   resplit hands are not removed when conditioning on the dealer not having blackjack, and are
+///   not refunded when only the original bets are lost.
+#[must_use]
+pub fn analyze_hand(
+    rules: &Rules,
+    composition: &Composition,
+    player_hand: &PlayerHand,
+    up_card: u8,
+) -> HandAnalysis {
+    let mut analyzer = Analyzer::new(rules, up_card);
+    let (total, soft) = (player_hand.value.total, player_hand.value.soft);
+    let two_cards = player_hand.size() == 2;
+    let double_allowed = (!player_hand.from_split || rules.double_after_split)
+        && rules.double_restriction.allows(player_hand.size(), total, soft);
+    let double_after_hitting = (!player_hand.from_split || rules.double_after_split)
+        && rules.double_restriction == DoubleRestriction::AnyCards;
+    let pair_card = player_hand.cards[0].rank.worth();
+    let split_allowed = player_hand.is_pair_by(rules.pair_definition)
+        && rules.max_splits_of_value(pair_card) != Some(0)
+        && (pair_card != 11 || rules.split_aces);
+    // The analyzer lets the dealer draw their hole card after the player, which is equivalent
+    // since the order in which unseen cards are drawn does not matter. A dealer blackjack costs
+    // the player their bet (k = 1) or their doubled bet (k = 2), and removing those outcomes
+    // gives the expected value after the dealer has peeked.
+    let blackjack = if up_card >= 10 {
+        analyzer.dealer(composition).blackjack
+    } else {
+        0.0
+    };
+    let peeked = |ev: f64, k: f64| match (rules.dealer_peeks(up_card), rules.original_bets_only) {
+        (true, _) => (ev + blackjack * k) / (1.0 - blackjack),
+        (false, false) => ev,
+        (false, true) => ev + blackjack * (k - 1.0),
+    };
+    HandAnalysis {
+        stand: peeked(analyzer.stand(composition, total).ev, 1.0),
+        hit: peeked(analyzer.hit(composition, total, soft, double_after_hitting).ev, 1.0),
+        double: double_allowed.then(|| {
+            if two_cards {
+                peeked(analyzer.double_two_cards(composition, total, soft).ev, 2.0)
+            } else {
+                peeked(analyzer.double(composition, total, soft).ev, 2.0)
+            }
+        }),
+        split: split_allowed.then(|| peeked(analyzer.split(composition, pair_card), 2.0)),
+        // Surrendering without a peek still loses the whole bet to a dealer blackjack
+        surrender: (two_cards && rules.late_surrender).then(|| {
+            if rules.dealer_peeks(up_card) {
+                -0.5
+            } else {
+                -0.5 * (1.0 - blackjack) - blackjack
+            }
+        }),
+    }
+}
+
+/// Calculates the expected values of the player's hands by recursively enumerating the cards left
+/// in the shoe. Dealer outcomes and the values of hands which are played on are remembered by the
+/// composition of the shoe, since the same cards can be drawn in different orders.
+struct Analyzer<'a> {
+    rules: &'a Rules,
+    up_card: u8,
+    dealer: HashMap<Composition, DealerOutcomes>,
+    best: HashMap<(Composition, u8, bool, bool), Outcome>,
+}
+
+impl<'a> Analyzer<'a> {
+    fn new(rules: &'a Rules, up_card: u8) -> Self {
+        Self {
+            rules,
+            up_card,
+            dealer: HashMap::new(),
+            best: HashMap::new(),
+        }
+    }
+
+    fn dealer(&mut self, composition: &Composition) -> DealerOutcomes {
+        let (up_card, rules) = (self.up_card, self.rules);
+        *self
+            .dealer
+            .entry(*composition)
+            .or_insert_with(|| DealerOutcomes::calculate(composition, up_card, rules))
+    }
+
+    fn stand(&mut self, composition: &Composition, total: u8) -> Outcome {
+        let dealer = self.dealer(composition);
+        let ev = dealer.stand_ev(total);
+        Outcome { ev, loss: dealer.win_probability(total) - ev }
+    }
+
+    /// The outcome of hitting once and then playing on, doubling after hitting if allowed.
+    fn hit(
+        &mut self,
+        composition: &Composition,
+        total: u8,
+        soft: bool,
+        can_double: bool,
+    ) -> Outcome {
+        self.draw(composition, total, soft, |analyzer, composition, total, soft| {
+            analyzer.best(composition, total, soft, can_double)
+        })
+    }
+
+    fn double(&mut self, composition: &Composition, total: u8, soft: bool) -> Outcome {
+        let outcome = self.draw(composition, total, soft, |analyzer, composition, total, _| {
+            analyzer.stand(composition, total)
+        });
+        Outcome { ev: 2.0 * outcome.ev, ..outcome }
+    }
+
+    /// The outcome of a free double, which only loses the original bet but wins twice the bet.
+    fn free_double(&mut self, composition: &Composition, total: u8, soft: bool) -> Outcome {
+        self.draw(composition, total, soft, |analyzer, composition, total, _| {
+            let outcome = analyzer.stand(composition, total);
+            let win = analyzer.dealer(composition).win_probability(total);
+            Outcome { ev: outcome.ev + win, ..outcome }
+        })
+    }
+
+    /// The outcome of doubling down on a hand of two cards, which may be free.
+    fn double_two_cards(&mut self, composition: &Composition, total: u8, soft: bool) -> Outcome {
+        if self.rules.free_doubles && DoubleRestriction::NineToEleven.allows(2, total, soft) {
+            self.free_double(composition, total, soft)
+        } else {
+            self.double(composition, total, soft)
+        }
+    }
+
+    /// The expected value of splitting a pair, approximated from the outcome of one split hand.
+    /// Resplit hands are assumed to draw from the same shoe as the first split hand.
+    fn split(&mut self, composition: &Composition, card: u8) -> f64 {
+        let (can_hit, can_double) = split_hand_options(self.rules, card);
+        let (total, soft) = add_card(0, false, card);
+        let mut play_on = |analyzer: &mut Self, composition: &Composition, total, soft| {
+            if analyzer.rules.split_21_is_blackjack && soft && total == 21 {
+                // Like every other hand, this is counted as lost to a dealer blackjack,
+                // which is removed once the dealer has peeked
+                let blackjack = analyzer.dealer(composition).blackjack;
+                let ev = (1.0 - blackjack) * blackjack_payout(analyzer.rules) - blackjack;
+                return Outcome { ev, loss: blackjack };
+            }
+            let can_double = can_double && analyzer.rules.double_restriction.allows(2, total, soft);
+            let outcome = if can_hit {
+                analyzer.best(composition, total, soft, can_double)
+            } else {
+                analyzer.stand(composition, total)
+            };
+            if can_double {
+                outcome.max(analyzer.double_two_cards(composition, total, soft))
+            } else {
+                outcome
+            }
+        };
+        let hand = self.draw(composition, total, soft, &mut play_on);
+        if composition.count(card) == 0 {
+            return split_value(self.rules, card, hand, 0.0, Outcome::default());
+        }
+        let (pair_total, pair_soft) = add_card(total, soft, card);
+        let mut remaining = *composition;
+        remaining.remove(card);
+        let pair = play_on(self, &remaining, pair_total, pair_soft);
+        let resplit = resplit_probability(self.rules, card, composition.probability(card));
+        split_value(self.rules, card, hand.without(resplit, pair), resplit, pair)
+    }
+
+    /// The outcome of playing on optimally, by standing, hitting, or doubling if allowed.
+    fn best(
+        &mut self,
+        composition: &Composition,
+        total: u8,
+        soft: bool,
+        can_double: bool,
+    ) -> Outcome {
+        let key = (*composition, total, soft, can_double);
+        if let Some(outcome) = self.best.get(&key) {
+            return *outcome;
+        }
+        let mut outcome = self.stand(composition, total);
+        if total < 21 {
+            let double_after_hitting =
+                can_double && self.rules.double_restriction == DoubleRestriction::AnyCards;
+            outcome = outcome.max(self.hit(composition, total, soft, double_after_hitting));
+            if can_double {
+                outcome = outcome.max(self.double(composition, total, soft));
+            }
+        }
+        self.best.insert(key, outcome);
+        outcome
+    }
+
+    /// Draws every possible card onto the given hand, and returns the outcome of the resulting
+    /// hands, where busted hands are lost and other hands are evaluated with the given function.
+    fn draw(
+        &mut self,
+        composition: &Composition,
+        total: u8,
+        soft: bool,
+        mut evaluate: impl FnMut(&mut Self, &Composition, u8, bool) -> Outcome,
+    ) -> Outcome {
+        let remaining = f64::from(composition.total());
+        let mut outcome = Outcome::default();
+        for i in 0..VALUES {
+            let count = composition.counts[i];
+            if count == 0 {
+                continue;
+            }
+            let card = value(i);
+            let (total, soft) = add_card(total, soft, card);
+            let probability = f64::from(count) / remaining;
+            let next = if total > 21 {
+                Outcome::BUST
+            } else {
+                let mut composition = *composition;
+                composition.remove(card);
+                evaluate(self, &composition, total, soft)
+            };
+            outcome.ev += probability * next.ev;
+            outcome.loss += probability * next.loss;
+        }
+        outcome
+    }
+}
+
+/// Returns how much a blackjack pays per chip bet.
+fn blackjack_payout(rules: &Rules) -> f64 {
+    let (numerator, denominator) = rules.blackjack_payout.ratio();
+    f64::from(numerator) / f64::from(denominator)
+}
+
+/// The most hands a pair is split into when the rules do not limit splitting.
+/// Splitting into more hands than this is rare enough to be left out.
+const MAX_SPLIT_HANDS: usize = 8;
+
+/// Returns the probability of drawing a card which can be resplit onto a hand split from a pair of
+/// the given value, where `probability` is the probability of drawing a card of that value.
+/// If only cards of the same rank make a pair, only a quarter of the tens can be resplit.
+fn resplit_probability(rules: &Rules, card: u8, probability: f64) -> f64 {
+    if card == 10 && rules.pair_definition == PairDefinition::SameRank {
+        probability / 4.0
+    } else {
+        probability
+    }
+}
+
+/// Returns the expected value of splitting a pair of cards with the given value.
+/// `other` is the outcome of a split hand over every second card which cannot be resplit, weighted
+/// by its probability, and `pair` is the outcome of playing on after drawing a card which can be
+/// resplit, which is drawn with probability `resplit`.
+///
+/// Pairs are resplit whenever that is worth more than playing on and the rules allow it, assuming
+/// every split hand draws from the same shoe. The house puts up the bet on each hand added by a
+/// free split, so the player does not lose it when that hand loses.
+fn split_value(rules: &Rules, card: u8, other: Outcome, resplit: f64, pair: Outcome) -> f64 {
+    let max_hands = if card == 11 && !rules.resplit_aces {
+        2
+    } else {
+        rules
+            .max_splits_of_value(card)
+            .map_or(MAX_SPLIT_HANDS, |max| usize::from(max) + 1)
+            .clamp(2, MAX_SPLIT_HANDS)
+    };
+    let free = rules.free_splits && card != 10;
+    let value = |outcome: Outcome, paid: bool| {
+        if free && !paid {
+            outcome.ev + outcome.loss
+        } else {
+            outcome.ev
+        }
+    };
+    // values[n][k][paid] is the value of k split hands waiting for their second card, out of n
+    // hands in total, where paid is whether one of them still has the original bet. That hand is
+    // played first, and keeps the original bet when it is resplit.
+    let mut values = [[[0.0; 2]; MAX_SPLIT_HANDS + 1]; MAX_SPLIT_HANDS + 1];
+    for n in (2..=max_hands).rev() {
+        for k in 1..=n {
+            for paid in [false, true] {
+                let finished = values[n][k - 1][0];
+                let mut pair_value = value(pair, paid) + finished;
+                if n < max_hands {
+                    pair_value = pair_value.max(values[n + 1][k + 1][usize::from(paid)]);
+                }
+                values[n][k][usize::from(paid)] =
+                    value(other, paid) + (1.0 - resplit) * finished + resplit * pair_value;
+            }
+        }
+    }
+    values[2][2][1]
+}
+
+/// Returns whether a hand split from a pair of cards with the given value may be hit and doubled.
+fn split_hand_options(rules: &Rules, card: u8) -> (bool, bool) {
+    if card == 11 && rules.split_aces_one_card {
+        (false, false)
+    } else if card == 11 {
+        (rules.hit_split_aces, rules.hit_split_aces && rules.double_after_split)
+    } else {
+        (true, rules.double_after_split)
+    }
+}
+
+/// Calculates the approximate house edge of the given rules with the given number of decks,
+/// for a player following the optimal strategy for each starting hand.
+///
+/// Every combination of two player cards and dealer up card is enumerated with its exact
+/// probability, but the rest of the calculation is approximated:
+/// - Cards drawn after the starting cards come from a fixed shoe without the starting cards,
+///   rather than removing each drawn card in turn, so the player's strategy only depends on
+///   their hand total.
+/// - Splits are valued as in `TotalEvaluator::split`, with every split hand drawing from the same
+///   shoe.
+/// - A dealer blackjack revealed after splitting costs exactly both split bets, so doubles after
+///   splitting and resplit hands are not lost to it (see `extra_blackjack_loss`).
+/// - Insurance is never taken.
+#[must_use]
+pub fn house_edge(rules: &Rules, decks: u8) -> f64 {
+    let full = Composition::full(decks);
+    let mut ev = 0.0;
+    for up_card in 2..=11 {
+        let mut shoe = full;
+        let up_probability = shoe.probability(up_card);
+        shoe.remove(up_card);
+        for first in 2..=11 {
+            for second in 2..=11 {
+                let mut composition = shoe;
+                let mut probability = up_probability * composition.probability(first);
+                if probability == 0.0 {
+                    continue;
+                }
+                composition.remove(first);
+                probability *= composition.probability(second);
+                if probability == 0.0 {
+                    continue;
+                }
+                composition.remove(second);
+                ev += probability * starting_hand_ev(rules, &composition, up_card, first, second);
+            }
+        }
+    }
+    -ev
+}
+
+/// Returns how much more a doubled or split hand loses to a dealer blackjack than a hand which was
+/// stood on or hit, relative to the probability of the dealer not having blackjack.
+/// This is only the case if the dealer does not peek with the given up card, when the dealer's
+/// blackjack is revealed after the player has doubled or split, unless only original bets are lost.
+#[must_use]
+pub fn extra_blackjack_loss(rules: &Rules, up_card: u8, dealer: &DealerOutcomes) -> f64 {
+    if !rules.dealer_peeks(up_card) && !rules.original_bets_only && dealer.blackjack < 1.0 {
+        dealer.blackjack / (1.0 - dealer.blackjack)
+    } else {
+        0.0
+    }
+}
+
+/// The expected value of a starting hand, including the dealer's check for blackjack.
+fn starting_hand_ev(rules: &Rules, composition: &Composition, up_card: u8, first: u8, second: u8) -> f64 {
+    let dealer = DealerOutcomes::calculate(composition, up_card, rules);
+    let (total, soft) = add_card(0, false, first);
+    let (total, soft) = add_card(total, soft, second);
+    if soft && total == 21 {
+        return (1.0 - dealer.blackjack) * blackjack_payout(rules);
+    }
+    let evaluator = TotalEvaluator::new(composition.probabilities(), &dealer.without_blackjack(), rules);
+    let extra_loss = extra_blackjack_loss(rules, up_card, &dealer);
+    let mut best = evaluator.best(total, soft);
+    if rules.double_restriction.allows(2, total, soft) {
+        best = best.max(evaluator.double_two_cards(total, soft) - extra_loss);
+    }
+    // Pairs of ten-valued cards of different ranks can only be split if pairs are defined by value,
+    // but they are never worth splitting anyway
+    if first == second && rules.max_splits_of_value(first) != Some(0) && (first != 11 || rules.split_aces) {
+        best = best.max(evaluator.split(first, rules) - extra_loss);
+    }
+    if rules.late_surrender {
+        best = best.max(-0.5);
+    }
+    let ev = -dealer.blackjack + (1.0 - dealer.blackjack) * best;
+    if rules.early_surrender {
+        ev.max(-0.5)
+    } else {
+        ev
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Rank;
+    use crate::rules::Peek;
+    use crate::test_helpers::{card, hand_of};
+
+    fn analyze(ranks: [Rank; 2], up_card: Rank) -> HandAction {
+        let [first, second] = ranks;
+        let mut composition = Composition::full(6);
+        for rank in [&first, &second, &up_card] {
+            composition.remove(rank.worth());
+        }
+        let up_card = up_card.worth();
+        let player_hand = hand_of([first, second].map(card), 1);
+        analyze_hand(&Rules::default(), &composition, &player_hand, up_card).best().0
+    }
+
+    #[test]
+    fn test_dealer_outcomes_sum_to_one() {
+        let mut composition = Composition::full(6);
+        composition.remove(11);
+        let outcomes = DealerOutcomes::calculate(&composition, 11, &Rules::default());
+        let sum = outcomes.totals.iter().sum::<f64>() + outcomes.bust + outcomes.push_22 + outcomes.blackjack;
+        assert!((sum - 1.0).abs() < 1e-9);
+        // A dealer showing an ace has blackjack with the probability of drawing a ten
+        assert!((outcomes.blackjack - 96.0 / 311.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_house_edge_matches_published_value() {
+        // Six decks, dealer stands on soft 17, double after split, late surrender and splitting to
+        // four hands give a house edge of about 0.36%, but being allowed to hit split aces is worth
+        // about 0.19% to the player and resplitting aces about 0.08%
+        let edge = house_edge(&Rules::default(), 6);
+        assert!((0.0005..0.0011).contains(&edge), "house edge was {edge}");
+    }
+
+    #[test]
+    fn test_late_dealer_blackjack_costs_doubles_and_splits() {
+        let peek = house_edge(&Rules::default(), 6);
+        let enhc = Rules {
+            no_hole_card: true,
+            ..Rules::default()
+        };
+        let obo = Rules {
+            original_bets_only: true,
+            ..enhc.clone()
+        };
+        // Losing doubles and splits to a dealer blackjack is worth about 0.11% to the house
+        assert!((0.0008..0.0014).contains(&(house_edge(&enhc, 6) - peek)));
+        assert!((house_edge(&obo, 6) - peek).abs() < 1e-9);
+        // Not peeking with a hole card is the same as not having a hole card
+        let never = Rules {
+            peek: Peek::Never,
+            ..Rules::default()
+        };
+        assert!((house_edge(&never, 6) - house_edge(&enhc, 6)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_surrender_without_peek_loses_to_blackjack() {
+        let mut composition = Composition::full(6);
+        for value in [10, 6, 10] {
+            composition.remove(value);
+        }
+        let player_hand = hand_of([Rank::Ten, Rank::Six].map(card), 10);
+        let surrender = |rules: &Rules| analyze_hand(rules, &composition, &player_hand, 10).surrender;
+        assert_eq!(surrender(&Rules::default()), Some(-0.5));
+        let never = Rules {
+            peek: Peek::Never,
+            ..Rules::default()
+        };
+        let blackjack = composition.probability(11);
+        let expected = -0.5 * (1.0 - blackjack) - blackjack;
+        assert!(surrender(&never).is_some_and(|ev| (ev - expected).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_analyzer_agrees_with_basic_strategy() {
+        assert_eq!(analyze([Rank::Ten, Rank::Six], Rank::Ten), HandAction::Surrender);
+        assert_eq!(analyze([Rank::Ten, Rank::Two], Rank::Six), HandAction::Stand);
+        assert_eq!(analyze([Rank::Ten, Rank::Two], Rank::Two), HandAction::Hit);
+        assert_eq!(analyze([Rank::Six, Rank::Five], Rank::Six), HandAction::Double);
+        assert_eq!(analyze([Rank::Ace, Rank::Ace], Rank::Ten), HandAction::Split);
+    }
+
+    #[test]
+    fn test_resplits_add_value() {
+        let split = |max_splits, card| {
+            let rules = Rules {
+                max_splits,
+                ..Rules::default()
+            };
+            let mut composition = Composition::full(6);
+            composition.remove(6);
+            let dealer = DealerOutcomes::calculate(&composition, 6, &rules).without_blackjack();
+            TotalEvaluator::new(composition.probabilities(), &dealer, &rules).split(card, &rules)
+        };
+        // Resplitting eights against a six is worth more than playing on a pair of eights,
+        // but each resplit is less likely than the one before
+        assert!(split(Some(1), 8) < split(Some(2), 8));
+        assert!(split(Some(2), 8) - split(Some(1), 8) > split(Some(3), 8) - split(Some(2), 8));
+        assert!(split(Some(3), 8) < split(None, 8));
+        // Tens are better left standing than resplit
+        assert!((split(Some(1), 10) - split(None, 10)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_free_split_covers_only_lost_hands() {
+        let rules = Rules {
+            max_splits: Some(1),
+            ..Rules::default()
+        };
+        let free_rules = Rules {
+            free_splits: true,
+            ..rules.clone()
+        };
+        let mut composition = Composition::full(6);
+        composition.remove(10);
+        let dealer = DealerOutcomes::calculate(&composition, 10, &free_rules).without_blackjack();
+        let evaluator = TotalEvaluator::new(composition.probabilities(), &dealer, &free_rules);
+        let split = evaluator.split(8, &rules);
+        let free_split = evaluator.split(8, &free_rules);
+        // The free hand is worth its chance of losing, which is less than (1 - ev) / 2 as long as
+        // the hand can push
+        let hand = split / 2.0;
+        assert!(free_split > split);
+        assert!(free_split - split < (1.0 - hand) / 2.0);
+    }
+}
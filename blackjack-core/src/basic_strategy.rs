@@ -1,41 +1,81 @@
-#![allow(clippy::match_same_arms, clippy::unnested_or_patterns)]
+#![allow(clippy::match_same_arms, clippy::unnested_or_patterns, clippy::match_like_matches_macro)]
 
 //! Basic strategy for playing blackjack.
 //! This simulates a player who knows the optimal move for every possible hand.
 //! This makes a best-effort attempt to consider the rules of the game, but is not perfect.
 
-use crate::game::{HandAction, Table};
-use crate::card::hand::{DealerHand, PlayerHand, PlayerTurn};
+use crate::game::HandAction;
+use crate::card::hand::{PlayerHand, PlayerTurn};
+use crate::strategy::{DealerView, Strategy, TableView};
 use crate::composed;
 
+/// A player who follows basic strategy and always bets the same amount.
+/// Basic strategy has no tables for switching cards in Blackjack Switch, so this player never
+/// switches; `AdaptiveStrategy` decides when to switch from the expected values of both options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BasicStrategy;
+
+impl Strategy for BasicStrategy {
+    fn bet(&mut self, _table: TableView) -> u32 {
+        bet()
+    }
+
+    fn surrender_early(
+        &mut self,
+        table: TableView,
+        player_hand: &PlayerHand,
+        dealer_hand: DealerView,
+    ) -> bool {
+        surrender_early(table, player_hand, dealer_hand)
+    }
+
+    fn bet_insurance(
+        &mut self,
+        _table: TableView,
+        _player_hand: &PlayerHand,
+        _dealer_hand: DealerView,
+    ) -> u32 {
+        bet_insurance()
+    }
+
+    fn play_hand(
+        &mut self,
+        table: TableView,
+        player_turn: &PlayerTurn,
+        dealer_hand: DealerView,
+    ) -> HandAction {
+        play_hand(table, player_turn, dealer_hand)
+    }
+}
+
 #[must_use]
 pub const fn bet() -> u32 {
-    100 // TODO: Factor out betting strategy
+    100 // Other betting strategies can be combined with this one, see the betting module
 }
 
 #[must_use]
-pub fn surrender_late(table: &Table, player_hand: &PlayerHand, dealer_hand: &DealerHand) -> bool {
+pub fn surrender_late(table: TableView, player_hand: &PlayerHand, dealer_hand: DealerView) -> bool {
     match (player_hand.value.total, dealer_hand.showing()) {
-        (14, 10) => table.shoe.decks == 1 && player_hand.is_pair(),
-        (14, 11) => table.shoe.decks == 1 && player_hand.is_pair() && dealer_hand.hits_on_soft_17(),
-        (15, 10) if table.shoe.decks < 8 => composed!(player_hand => 9, 6; 10, 5),
-        (15, 10) if table.shoe.decks >= 8 => true,
-        (15, 11) if table.shoe.decks < 4 => dealer_hand.hits_on_soft_17() && composed!(player_hand => 9, 6; 10, 5),
-        (15, 11) if table.shoe.decks >= 4 => true,
-        (16, 9) => table.shoe.decks >= 4,
+        (14, 10) => table.shoe().decks() == 1 && composed!(player_hand => 7, 7),
+        (14, 11) => table.shoe().decks() == 1 && composed!(player_hand => 7, 7) && dealer_hand.hits_on_soft_17(),
+        (15, 10) if table.shoe().decks() < 8 => composed!(player_hand => 9, 6; 10, 5),
+        (15, 10) if table.shoe().decks() >= 8 => true,
+        (15, 11) if table.shoe().decks() < 4 => dealer_hand.hits_on_soft_17() && composed!(player_hand => 9, 6; 10, 5),
+        (15, 11) if table.shoe().decks() >= 4 => true,
+        (16, 9) => table.shoe().decks() >= 4,
         (16, 10) => true,
-        (16, 11) if table.shoe.decks == 1 && !dealer_hand.hits_on_soft_17() => composed!(player_hand => 10, 6),
-        (16, 11) if table.shoe.decks <= 2 && dealer_hand.hits_on_soft_17() => composed!(player_hand => 9, 7; 10, 6),
-        (16, 11) if table.shoe.decks == 2 && !dealer_hand.hits_on_soft_17() => true,
-        (16, 11) if table.shoe.decks > 2 => true,
+        (16, 11) if table.shoe().decks() == 1 && !dealer_hand.hits_on_soft_17() => composed!(player_hand => 10, 6),
+        (16, 11) if table.shoe().decks() <= 2 && dealer_hand.hits_on_soft_17() => composed!(player_hand => 9, 7; 10, 6),
+        (16, 11) if table.shoe().decks() == 2 && !dealer_hand.hits_on_soft_17() => true,
+        (16, 11) if table.shoe().decks() > 2 => true,
         (15 | 17, 11) => dealer_hand.hits_on_soft_17(),
         _ => false,
     }
 }
 
 #[must_use]
-pub fn surrender_early(table: &Table, player_hand: &PlayerHand, dealer_hand: &DealerHand) -> bool {
-    match (player_hand.value.soft, player_hand.is_pair()) {
+pub fn surrender_early(table: TableView, player_hand: &PlayerHand, dealer_hand: DealerView) -> bool {
+    match (player_hand.value.soft, player_hand.is_pair_by(table.rules().pair_definition)) {
         (false, false) => surrender_early_hard(player_hand, dealer_hand),
         (true, false) => false, // Soft (non-pair) hands should not be surrendered early
         (_, true) => surrender_early_pair(player_hand, dealer_hand, table),
@@ -43,7 +83,7 @@ pub fn surrender_early(table: &Table, player_hand: &PlayerHand, dealer_hand: &De
 }
 
 /// Source: <https://wizardofodds.com/games/blackjack/surrender/>
-fn surrender_early_hard(player_hand: &PlayerHand, dealer_hand: &DealerHand) -> bool {
+fn surrender_early_hard(player_hand: &PlayerHand, dealer_hand: DealerView) -> bool {
     match (player_hand.value.total, dealer_hand.showing()) {
         (5..=7, 11) => true,
         (12..=17, 11) => true,
@@ -53,9 +93,9 @@ fn surrender_early_hard(player_hand: &PlayerHand, dealer_hand: &DealerHand) -> b
 }
 
 /// Source: <https://wizardofodds.com/games/blackjack/surrender/>
-fn surrender_early_pair(player_hand: &PlayerHand, dealer_hand: &DealerHand, table: &Table) -> bool {
-    match (player_hand.value.total / 2, dealer_hand.showing()) {
-        (8, 10) if table.shoe.decks == 1 && table.rules.double_after_split => false,
+fn surrender_early_pair(player_hand: &PlayerHand, dealer_hand: DealerView, table: TableView) -> bool {
+    match (player_hand.cards[0].rank.worth(), dealer_hand.showing()) {
+        (8, 10) if table.shoe().decks() == 1 && table.rules().double_after_split => false,
         (7..=8, 10) | (3 | 6..=8, 11) => true,
         (2, 11) if dealer_hand.hits_on_soft_17() => true,
         _ => false,
@@ -68,7 +108,8 @@ pub const fn bet_insurance() -> u32 {
 }
 
 /// The preferred action which may involve a fallback action
-enum PreferredAction {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreferredAction {
     Stand,
     Hit,
     Split,
@@ -82,12 +123,42 @@ enum PreferredAction {
 
 /// Assuming 4-8 decks
 #[must_use]
-pub fn play_hand(table: &Table, player_hands: &PlayerTurn, dealer_hand: &DealerHand) -> HandAction {
+pub fn play_hand(table: TableView, player_hands: &PlayerTurn, dealer_hand: DealerView) -> HandAction {
+    if player_hands.current_hand().doubled {
+        return rescue_doubled_hand(player_hands.current_hand(), dealer_hand);
+    }
     let preferred = match (player_hands.current_hand().value.soft, table.check_split_allowed(player_hands).is_ok()) {
-        (false, false) => make_move_hard(table, &player_hands.current_hand(), dealer_hand),
-        (true, false) => make_move_soft(&player_hands.current_hand(), dealer_hand),
-        (_, true) => make_move_splittable(&player_hands.current_hand(), dealer_hand),
+        (false, false) => make_move_hard(table, player_hands.current_hand(), dealer_hand),
+        (true, false) => make_move_soft(player_hands.current_hand(), dealer_hand),
+        (_, true) => make_move_splittable(player_hands.current_hand(), dealer_hand),
     };
+    resolve(table, player_hands, preferred)
+}
+
+/// A doubled hand is only still in play if it may be rescued by surrendering it.
+/// Rescuing loses half the doubled bet, which is better than standing on a stiff hand against a
+/// dealer who rarely busts.
+#[must_use]
+pub fn rescue_doubled_hand(player_hand: &PlayerHand, dealer_hand: DealerView) -> HandAction {
+    if player_hand.value.total <= 16 && dealer_hand.showing() >= 8 {
+        HandAction::Surrender
+    } else {
+        HandAction::Stand
+    }
+}
+
+/// Turns a preferred action into an action which is allowed for the current hand,
+/// falling back to the alternative if the preferred action is not allowed.
+/// Split aces which may not be hit are stood on instead.
+#[must_use]
+pub fn resolve(table: TableView, player_hands: &PlayerTurn, preferred: PreferredAction) -> HandAction {
+    match resolve_preferred(table, player_hands, preferred) {
+        HandAction::Hit if table.check_hit_allowed(player_hands).is_err() => HandAction::Stand,
+        action => action,
+    }
+}
+
+fn resolve_preferred(table: TableView, player_hands: &PlayerTurn, preferred: PreferredAction) -> HandAction {
     match preferred {
         PreferredAction::Stand => HandAction::Stand,
         PreferredAction::Hit => HandAction::Hit,
@@ -107,28 +178,28 @@ pub fn play_hand(table: &Table, player_hands: &PlayerTurn, dealer_hand: &DealerH
             }
         }
         PreferredAction::SurrenderOrHit => {
-            if table.check_surrender_allowed(&player_hands.current_hand()).is_ok() {
+            if table.check_surrender_allowed(player_hands.current_hand()).is_ok() {
                 HandAction::Surrender
             } else {
                 HandAction::Hit
             }
         }
         PreferredAction::SurrenderOrStand => {
-            if table.check_surrender_allowed(&player_hands.current_hand()).is_ok() {
+            if table.check_surrender_allowed(player_hands.current_hand()).is_ok() {
                 HandAction::Surrender
             } else {
                 HandAction::Stand
             }
         }
         PreferredAction::SurrenderOrSplit => {
-            if table.check_surrender_allowed(&player_hands.current_hand()).is_ok() {
+            if table.check_surrender_allowed(player_hands.current_hand()).is_ok() {
                 HandAction::Surrender
             } else {
                 HandAction::Split
             }
         }
         PreferredAction::SplitIfDoubleAfterSplitAllowedElseHit => {
-            if table.rules.double_after_split {
+            if table.rules().double_after_split {
                 HandAction::Split
             } else {
                 HandAction::Hit
@@ -138,19 +209,19 @@ pub fn play_hand(table: &Table, player_hands: &PlayerTurn, dealer_hand: &DealerH
 }
 
 fn make_move_hard(
-    table: &Table,
+    table: TableView,
     player_hand: &PlayerHand,
-    dealer_hand: &DealerHand,
+    dealer_hand: DealerView,
 ) -> PreferredAction {
     match (player_hand.value.total, dealer_hand.showing()) {
         (4..=8, 2..=11) => PreferredAction::Hit,
-        (9, 2) => if table.shoe.decks <= 2 { PreferredAction::DoubleOrHit } else { PreferredAction::Hit },
+        (9, 2) => if table.shoe().decks() <= 2 { PreferredAction::DoubleOrHit } else { PreferredAction::Hit },
         (9, 3..=6) => PreferredAction::DoubleOrHit,
         (9, 7..=11) => PreferredAction::Hit,
         (10, 2..=9) => PreferredAction::DoubleOrHit,
         (10, 10 | 11) => PreferredAction::Hit,
         (11, 2..=10) => PreferredAction::DoubleOrHit,
-        (11, 11) => if table.shoe.decks <= 2 || dealer_hand.hits_on_soft_17() { PreferredAction::DoubleOrHit } else { PreferredAction::Hit },
+        (11, 11) => if table.shoe().decks() <= 2 || dealer_hand.hits_on_soft_17() { PreferredAction::DoubleOrHit } else { PreferredAction::Hit },
         (12, 2..=3) => PreferredAction::Hit,
         (12, 4..=6) => PreferredAction::Stand,
         (12..=14, 7..=11) => PreferredAction::Hit,
@@ -170,8 +241,9 @@ fn make_move_hard(
     }
 }
 
-fn make_move_soft(player_hand: &PlayerHand, dealer_hand: &DealerHand) -> PreferredAction {
+fn make_move_soft(player_hand: &PlayerHand, dealer_hand: DealerView) -> PreferredAction {
     match (player_hand.value.total, dealer_hand.showing()) {
+        (12, 2..=11) => PreferredAction::Hit, // A pair of aces which may not be split
         (13 | 14, 2..=4) => PreferredAction::Hit,
         (13 | 14, 5 | 6) => PreferredAction::DoubleOrHit,
         (15 | 16, 2 | 3) => PreferredAction::Hit,
@@ -196,9 +268,9 @@ fn make_move_soft(player_hand: &PlayerHand, dealer_hand: &DealerHand) -> Preferr
 
 fn make_move_splittable(
     player_hand: &PlayerHand,
-    dealer_hand: &DealerHand
+    dealer_hand: DealerView
 ) -> PreferredAction {
-    match (player_hand.value.total / 2, dealer_hand.showing()) {
+    match (player_hand.cards[0].rank.worth(), dealer_hand.showing()) {
         (2 | 3, 2 | 3) => PreferredAction::SplitIfDoubleAfterSplitAllowedElseHit,
         (2 | 3, 4..=7) => PreferredAction::Split,
         (2 | 3, 8..=11) => PreferredAction::Hit,
@@ -0,0 +1,424 @@
+//! Betting strategies.
+//! A betting strategy only decides how much to bet at the start of each round, and can be combined
+//! with any playing strategy using [`WithBetting`].
+//! Betting strategies express their bets in chips, and [`WithBetting`] limits every bet to the
+//! table's minimum and maximum bets and to the chips the player has left.
+//! A bet of 0 means sitting out the round, which only [`Wonging`] does.
+
+use crate::card::hand::{PlayerHand, PlayerTurn};
+use crate::counting::CountSystem;
+use crate::game::HandAction;
+use crate::strategy::{DealerView, Strategy, TableView};
+
+/// A strategy for sizing bets.
+pub trait BettingStrategy {
+    /// Decide how much to bet at the start of a round, or 0 to sit out the round.
+    fn bet(&mut self, table: TableView) -> u32;
+}
+
+/// Limits a bet to the table's minimum and maximum bets and to the chips the player has left.
+/// A bet of 0 is kept as is, since it means sitting out the round.
+#[must_use]
+pub fn limit_bet(table: TableView, bet: u32) -> u32 {
+    if bet == 0 {
+        return 0;
+    }
+    let bet = table.rules().min_bet.map_or(bet, |min| bet.max(min));
+    let bet = table.rules().max_bet.map_or(bet, |max| bet.min(max));
+    bet.min(table.chips())
+}
+
+/// A player who plays their hands with one strategy and sizes their bets with another.
+/// The playing strategy still decides which spots to play and which side bets to place,
+/// but the betting strategy sizes the bet on every spot.
+#[derive(Debug, Clone, Default)]
+pub struct WithBetting<S, B> {
+    /// The strategy used to play hands
+    pub strategy: S,
+    /// The strategy used to size bets
+    pub betting: B,
+}
+
+impl<S, B> WithBetting<S, B> {
+    pub const fn new(strategy: S, betting: B) -> Self {
+        Self { strategy, betting }
+    }
+}
+
+impl<S: Strategy, B: BettingStrategy> Strategy for WithBetting<S, B> {
+    fn bet(&mut self, table: TableView) -> u32 {
+        limit_bet(table, self.betting.bet(table))
+    }
+
+    fn bet_spots(&mut self, table: TableView) -> Vec<u32> {
+        let bet = self.bet(table);
+        self.strategy
+            .bet_spots(table)
+            .into_iter()
+            .map(|spot_bet| if spot_bet == 0 { 0 } else { bet })
+            .collect()
+    }
+
+    fn side_bets(&mut self, table: TableView) -> Vec<u32> {
+        self.strategy.side_bets(table)
+    }
+
+    fn surrender_early(
+        &mut self,
+        table: TableView,
+        player_hand: &PlayerHand,
+        dealer_hand: DealerView,
+    ) -> bool {
+        self.strategy.surrender_early(table, player_hand, dealer_hand)
+    }
+
+    fn bet_insurance(
+        &mut self,
+        table: TableView,
+        player_hand: &PlayerHand,
+        dealer_hand: DealerView,
+    ) -> u32 {
+        self.strategy.bet_insurance(table, player_hand, dealer_hand)
+    }
+
+    fn take_even_money(
+        &mut self,
+        table: TableView,
+        player_hand: &PlayerHand,
+        dealer_hand: DealerView,
+    ) -> bool {
+        self.strategy.take_even_money(table, player_hand, dealer_hand)
+    }
+
+    fn switch_cards(
+        &mut self,
+        table: TableView,
+        player_hands: &[PlayerHand],
+        dealer_hand: DealerView,
+    ) -> bool {
+        self.strategy.switch_cards(table, player_hands, dealer_hand)
+    }
+
+    fn play_hand(
+        &mut self,
+        table: TableView,
+        player_turn: &PlayerTurn,
+        dealer_hand: DealerView,
+    ) -> HandAction {
+        self.strategy.play_hand(table, player_turn, dealer_hand)
+    }
+}
+
+/// Always bets the same amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlatBet(pub u32);
+
+impl BettingStrategy for FlatBet {
+    fn bet(&mut self, _table: TableView) -> u32 {
+        self.0
+    }
+}
+
+/// Bets a number of units depending on the true count.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BetRamp {
+    /// The count system used to calculate the true count
+    pub system: CountSystem,
+    /// The size of one unit in chips
+    pub unit: u32,
+    /// The number of units to bet from each true count upwards, in ascending order of true count.
+    /// Below the first true count, a single unit is bet.
+    pub steps: Vec<(f32, u32)>,
+}
+
+impl BetRamp {
+    /// A common 1-12 spread for a six-deck shoe using Hi-Lo.
+    #[must_use]
+    pub fn hi_lo(unit: u32) -> Self {
+        Self {
+            system: CountSystem::HI_LO,
+            unit,
+            steps: vec![(2.0, 2), (3.0, 4), (4.0, 8), (5.0, 12)],
+        }
+    }
+
+    /// Returns the number of units to bet at the given true count.
+    #[must_use]
+    pub fn units(&self, true_count: f32) -> u32 {
+        self.steps
+            .iter()
+            .take_while(|(count, _)| true_count >= *count)
+            .last()
+            .map_or(1, |(_, units)| *units)
+    }
+}
+
+impl BettingStrategy for BetRamp {
+    fn bet(&mut self, table: TableView) -> u32 {
+        self.unit * self.units(self.system.true_count(table.shoe()))
+    }
+}
+
+/// Bets a fraction of the bankroll proportional to the player's estimated advantage,
+/// which maximizes the long-term growth of the bankroll.
+/// The advantage is estimated from the true count as `advantage + advantage_per_true_count × true count`.
+/// If the player has no advantage, the minimum bet is placed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Kelly {
+    /// The count system used to calculate the true count
+    pub system: CountSystem,
+    /// The fraction of the full Kelly bet to place, e.g. 0.5 for half Kelly
+    pub fraction: f64,
+    /// The player's advantage at a true count of 0, usually minus the house edge
+    pub advantage: f64,
+    /// The increase in the player's advantage for every point of true count
+    pub advantage_per_true_count: f64,
+    /// The variance of the result of a round, in squared initial bets
+    pub variance: f64,
+}
+
+impl Kelly {
+    /// Half Kelly for a six-deck shoe using Hi-Lo, where every true count is worth about 0.5%.
+    #[must_use]
+    pub const fn hi_lo() -> Self {
+        Self {
+            system: CountSystem::HI_LO,
+            fraction: 0.5,
+            advantage: -0.005,
+            advantage_per_true_count: 0.005,
+            variance: 1.33,
+        }
+    }
+}
+
+impl BettingStrategy for Kelly {
+    fn bet(&mut self, table: TableView) -> u32 {
+        let true_count = f64::from(self.system.true_count(table.shoe()));
+        let advantage = self.advantage + self.advantage_per_true_count * true_count;
+        let bet = self.fraction * f64::from(table.chips()) * advantage / self.variance;
+        // Bets are rounded down to whole chips, and the minimum bet is placed without an advantage
+        bet.max(1.0) as u32
+    }
+}
+
+/// Only plays when the count is favourable, and sits out the round otherwise.
+/// The player watches the shoe being dealt and starts playing once the true count reaches `enter`,
+/// then keeps playing until the true count drops below `exit`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wonging<B> {
+    /// The count system used to calculate the true count
+    pub system: CountSystem,
+    /// The true count at which the player starts playing
+    pub enter: f32,
+    /// The true count below which the player stops playing
+    pub exit: f32,
+    /// The betting strategy used while playing
+    pub betting: B,
+    /// Whether the player is currently playing
+    playing: bool,
+}
+
+impl<B> Wonging<B> {
+    #[must_use]
+    pub const fn new(system: CountSystem, enter: f32, exit: f32, betting: B) -> Self {
+        Self {
+            system,
+            enter,
+            exit,
+            betting,
+            playing: false,
+        }
+    }
+}
+
+impl<B: BettingStrategy> BettingStrategy for Wonging<B> {
+    fn bet(&mut self, table: TableView) -> u32 {
+        let true_count = self.system.true_count(table.shoe());
+        self.playing = if self.playing {
+            true_count >= self.exit
+        } else {
+            true_count >= self.enter
+        };
+        if self.playing {
+            self.betting.bet(table)
+        } else {
+            0
+        }
+    }
+}
+
+/// Keeps track of the player's net result, to find out how the last round went.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct LastRound {
+    net: Option<i64>,
+}
+
+impl LastRound {
+    /// Returns the net result of the round played since the last call, or None on the first call.
+    fn result(&mut self, table: TableView) -> Option<i64> {
+        let statistics = &table.statistics();
+        let net = statistics.total_won() as i64 - statistics.total_bet() as i64;
+        self.net.replace(net).map(|last| net - last)
+    }
+}
+
+/// Doubles the bet after every loss, and returns to the base bet after a win.
+/// This is a negative progression, shown here for comparison: it does not change the house edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Martingale {
+    /// The base bet in chips
+    pub unit: u32,
+    bet: u32,
+    last_round: LastRound,
+}
+
+impl Martingale {
+    #[must_use]
+    pub const fn new(unit: u32) -> Self {
+        Self {
+            unit,
+            bet: unit,
+            last_round: LastRound { net: None },
+        }
+    }
+}
+
+impl BettingStrategy for Martingale {
+    fn bet(&mut self, table: TableView) -> u32 {
+        match self.last_round.result(table).map(i64::signum) {
+            Some(-1) => self.bet = self.bet.saturating_mul(2),
+            Some(1) => self.bet = self.unit,
+            _ => {}
+        }
+        self.bet
+    }
+}
+
+/// Doubles the bet after every win, and returns to the base bet after a loss
+/// or after `wins` wins in a row.
+/// This is a positive progression, shown here for comparison: it does not change the house edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Paroli {
+    /// The base bet in chips
+    pub unit: u32,
+    /// The number of wins in a row after which the progression starts over.
+    /// A value of 0 is treated as 1, i.e. always betting one unit.
+    pub wins: u32,
+    streak: u32,
+    last_round: LastRound,
+}
+
+impl Paroli {
+    #[must_use]
+    pub const fn new(unit: u32) -> Self {
+        Self {
+            unit,
+            wins: 3,
+            streak: 0,
+            last_round: LastRound { net: None },
+        }
+    }
+}
+
+impl BettingStrategy for Paroli {
+    fn bet(&mut self, table: TableView) -> u32 {
+        match self.last_round.result(table).map(i64::signum) {
+            Some(-1) => self.streak = 0,
+            Some(1) => self.streak = (self.streak + 1) % self.wins.max(1),
+            _ => {}
+        }
+        self.unit.saturating_mul(2_u32.saturating_pow(self.streak))
+    }
+}
+
+/// Bets 1, 3, 2 and then 6 units on consecutive wins, and returns to 1 unit after a loss
+/// or after completing the sequence.
+/// This is a positive progression, shown here for comparison: it does not change the house edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OneThreeTwoSix {
+    /// The base bet in chips
+    pub unit: u32,
+    step: usize,
+    last_round: LastRound,
+}
+
+impl OneThreeTwoSix {
+    const UNITS: [u32; 4] = [1, 3, 2, 6];
+
+    #[must_use]
+    pub const fn new(unit: u32) -> Self {
+        Self {
+            unit,
+            step: 0,
+            last_round: LastRound { net: None },
+        }
+    }
+}
+
+impl BettingStrategy for OneThreeTwoSix {
+    fn bet(&mut self, table: TableView) -> u32 {
+        match self.last_round.result(table).map(i64::signum) {
+            Some(-1) => self.step = 0,
+            Some(1) => self.step = (self.step + 1) % Self::UNITS.len(),
+            _ => {}
+        }
+        self.unit * Self::UNITS[self.step]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::basic_strategy::BasicStrategy;
+    use crate::card::shoe::Shoe;
+    use crate::game::{Input, Table, Wager};
+    use crate::rules::Rules;
+    use crate::side_bets::{PerfectPairs, WithSideBets};
+    use crate::simulation::{Budget, Progress, Simulation};
+    use crate::state::GameState;
+
+    #[test]
+    fn test_bet_ramp_units() {
+        let ramp = BetRamp::hi_lo(10);
+        assert_eq!(ramp.units(-3.0), 1);
+        assert_eq!(ramp.units(2.5), 2);
+        assert_eq!(ramp.units(4.0), 8);
+        assert_eq!(ramp.units(9.0), 12);
+    }
+
+    #[test]
+    fn test_wonging_sits_out_most_rounds() {
+        let simulation = Simulation {
+            budget: Budget::Rounds(2_000),
+            threads: 1,
+            seed: Some(11),
+            ..Simulation::default()
+        }
+        .with_strategy(WithBetting::new(
+            BasicStrategy,
+            Wonging::new(CountSystem::HI_LO, 1.0, 0.0, FlatBet(100)),
+        ));
+        let progress = Progress::new();
+        let result = simulation.run(&progress).unwrap();
+        // Rounds sat out count towards progress, but not towards the budget
+        assert_eq!(result.statistics.turns_played(), 2_000);
+        assert!(progress.rounds_played() > 2_000);
+        assert!(result.statistics.total_bet() >= 2_000 * 100);
+    }
+
+    #[test]
+    fn test_side_bets_are_kept_with_betting() {
+        let rules = Rules {
+            side_bets: vec![Arc::new(PerfectPairs::default())],
+            ..Rules::default()
+        };
+        let table = Table::new(1_000, Shoe::with_seed(6, 0.75, 7), rules);
+        let mut strategy = WithBetting::new(WithSideBets::new(BasicStrategy, vec![5]), FlatBet(200));
+        let Some(Input::Wagers(wagers)) = crate::strategy::input(&mut strategy, &table, &GameState::Betting) else {
+            panic!("the side bets should have been placed");
+        };
+        assert_eq!(wagers, vec![Wager { bet: 200, side_bets: vec![5] }]);
+    }
+}
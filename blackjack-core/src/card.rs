@@ -88,7 +88,8 @@ impl Card {
     /// # Panics
     ///
     /// Panics if `ordinal` is >= 52
-    fn from_ordinal(ordinal: usize) -> Self {
+    #[must_use]
+    pub fn from_ordinal(ordinal: usize) -> Self {
         let rank = match ordinal / 4 {
             0 => Rank::Two,
             1 => Rank::Three,
@@ -114,6 +115,12 @@ impl Card {
         };
         Self { rank, suit }
     }
+
+    /// Returns the ordinal value (0-51) of this card, the inverse of [`Card::from_ordinal`].
+    #[must_use]
+    pub fn ordinal(&self) -> usize {
+        self.rank.clone() as usize * 4 + self.suit.clone() as usize
+    }
 }
 
 pub mod hand {
@@ -121,11 +128,12 @@ pub mod hand {
     use std::fmt;
     use std::ops::AddAssign;
 
-    use crate::card::{Card, Rank};
-    use crate::rules::{BlackjackPayout, DealerSoft17Action};
+    use crate::card::{Card, Rank, Suit};
+    use crate::rules::{DealerSoft17Action, PairDefinition, Rules, SuitedBonus};
+    use crate::side_bets::SpotSideBets;
 
     /// Represents the game value of a hand, e.g. "Soft 20"
-    #[derive(Debug, Default, PartialEq, Eq)]
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
     pub struct Value {
         /// Whether the hand has an ace that is currently worth 11
         pub soft: bool,
@@ -178,7 +186,9 @@ pub mod hand {
     }
 
     /// Represents the status of a hand.
-    /// A hand may still be in play, or it may be in any of the four terminal states.
+    /// A hand may still be in play, or it may be in any of the five terminal states.
+    /// Only a player hand can be a Charlie, which has reached the number of cards that wins
+    /// automatically under the rules without busting.
     #[derive(Debug, Clone, Default, PartialEq, Eq)]
     pub enum Status {
         #[default]
@@ -187,10 +197,32 @@ pub mod hand {
         Bust,
         Blackjack,
         Surrendered,
+        Charlie,
+    }
+
+    /// How the suits of the cards in a hand match, which some bonuses depend on.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Suits {
+        Mixed,
+        Suited,
+        Spades,
+    }
+
+    /// A special hand which is paid a bonus when it wins.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Bonus {
+        /// 21 with five or more cards, given the number of cards
+        ManyCard21(u8),
+        /// 21 made of a six, a seven and an eight
+        SixSevenEight(Suits),
+        /// 21 made of three sevens
+        SevenSevenSeven(Suits),
+        /// A blackjack of a single suit
+        SuitedBlackjack(Suits),
     }
 
     /// Represents the dealer's hand.
-    #[derive(Debug, PartialEq, Eq)]
+    #[derive(Debug, Clone, PartialEq, Eq)]
     pub struct DealerHand {
         /// The value of this hand
         pub value: Value,
@@ -236,6 +268,24 @@ pub mod hand {
             self.cards[0].rank.worth()
         }
 
+        /// Returns the dealer's up card.
+        #[must_use]
+        pub fn up_card(&self) -> &Card {
+            &self.cards[0]
+        }
+
+        /// Returns the dealer's hole card, if it has been dealt.
+        #[must_use]
+        pub(crate) fn hole_card(&self) -> Option<&Card> {
+            self.cards.get(1)
+        }
+
+        /// Returns the number of cards in the dealer's hand.
+        #[must_use]
+        pub fn size(&self) -> usize {
+            self.cards.len()
+        }
+
         /// Returns whether the dealer hits on soft 17.
         #[must_use]
         pub fn hits_on_soft_17(&self) -> bool {
@@ -254,8 +304,23 @@ pub mod hand {
         pub status: Status,
         /// The cards in this hand (non-empty at all times)
         pub cards: Vec<Card>,
-        /// The player's winnings on this hand
+        /// The player's winnings on this hand, before the house takes back any free bet
         pub winnings: u32,
+        /// The part of the bet put up by the house for a free double or split, as in Free Bet
+        /// blackjack. The player does not lose it, but it is taken back from the winnings.
+        pub free_bet: u32,
+        /// Whether the player doubled down on this hand
+        pub doubled: bool,
+        /// Whether this hand was split from a pair
+        pub from_split: bool,
+        /// Whether the player took even money for this blackjack
+        pub even_money: bool,
+        /// The bonus paid on this hand, once it has been settled
+        pub paid_bonus: Option<Bonus>,
+        /// The spot this hand was dealt to, or split from, if the player plays several spots
+        pub spot: u8,
+        /// The side bets placed on the spot, if any, which are kept by the first hand of the spot
+        pub side_bets: Option<Box<SpotSideBets>>,
     }
 
     impl AddAssign<Card> for PlayerHand {
@@ -266,7 +331,7 @@ pub mod hand {
             self.cards.push(rhs);
             self.status = match self.value.total {
                 22.. => Status::Bust,
-                21 if self.size() == 2 => Status::Blackjack,
+                21 if self.size() == 2 && !self.from_split => Status::Blackjack,
                 21 => Status::Stood,
                 _ => Status::InPlay,
             }
@@ -283,6 +348,13 @@ pub mod hand {
                 status: Status::InPlay,
                 cards: vec![card],
                 winnings: 0,
+                free_bet: 0,
+                doubled: false,
+                from_split: false,
+                even_money: false,
+                paid_bonus: None,
+                spot: 0,
+                side_bets: None,
             }
         }
 
@@ -296,10 +368,19 @@ pub mod hand {
         /// The bet is doubled, and the provided card is added to the hand.
         /// If the hand is not bust, the player stands.
         pub fn double(&mut self, card: Card) {
-            debug_assert_eq!(
-                self.size(),
-                2,
-                "cannot double down on hand with more than two cards"
+            self.double_with_rescue(card);
+            // If the hand is not finished otherwise, the player stands
+            if self.status == Status::InPlay {
+                self.status = Status::Stood;
+            }
+        }
+
+        /// The player doubles down on this hand, but may still rescue it by surrendering
+        /// instead of standing. The hand stays in play unless it is 21 or bust.
+        pub fn double_with_rescue(&mut self, card: Card) {
+            debug_assert!(
+                self.size() >= 2,
+                "cannot double down on hand with fewer than two cards"
             );
             debug_assert_eq!(
                 self.status,
@@ -307,11 +388,8 @@ pub mod hand {
                 "cannot double down on finished hand"
             );
             self.bet *= 2;
+            self.doubled = true;
             *self += card;
-            // If the hand is not finished otherwise, the player stands
-            if self.status == Status::InPlay {
-                self.status = Status::Stood;
-            }
         }
 
         /// The player splits the hand into two hands. This hand must be a pair!
@@ -320,63 +398,192 @@ pub mod hand {
         /// Will panic if the hand is not a pair.
         #[must_use]
         pub fn split(&mut self) -> Self {
-            debug_assert!(self.is_pair(), "cannot split hand that is not a pair");
+            debug_assert!(
+                self.is_pair_by(PairDefinition::SameValue),
+                "cannot split hand that is not a pair"
+            );
             let split_card = self.cards.pop().expect("Hand must be a pair"); // Remove the second card
             self.value = Value::from(&self.cards[0]); // The value of this hand is now the first card
-            Self::new(split_card, self.bet) // Create a new hand with the second card
+            self.from_split = true;
+            let mut new_hand = Self::new(split_card, self.bet); // Create a new hand with the second card
+            new_hand.from_split = true;
+            new_hand.spot = self.spot;
+            new_hand
+        }
+
+        /// Switches the second card of this hand with the second card of the other hand,
+        /// as in Blackjack Switch. Both hands are valued again, so either may become a blackjack.
+        pub fn switch_second_card(&mut self, other: &mut Self) {
+            debug_assert!(
+                self.size() == 2 && other.size() == 2,
+                "cannot switch cards of hands without two cards"
+            );
+            std::mem::swap(&mut self.cards[1], &mut other.cards[1]);
+            for hand in [self, other] {
+                let second = hand.cards.pop().expect("Hand must have two cards");
+                hand.value = Value::from(&hand.cards[0]);
+                hand.status = Status::InPlay;
+                *hand += second;
+            }
         }
 
         /// The player surrenders this hand.
+        /// A doubled hand can only be surrendered if the rules allow double-down rescue.
         pub fn surrender(&mut self) {
-            debug_assert_eq!(
-                self.size(),
-                2,
+            debug_assert!(
+                self.size() == 2 || self.doubled,
                 "cannot surrender on hand with more than two cards"
             );
             self.status = Status::Surrendered;
         }
 
+        /// Returns the bet placed on this hand before any double down.
+        #[must_use]
+        pub const fn initial_bet(&self) -> u32 {
+            if self.doubled {
+                self.bet / 2
+            } else {
+                self.bet
+            }
+        }
+
+        /// Returns the part of the bet which the player paid for themselves, excluding any free bet.
+        #[must_use]
+        pub const fn paid_bet(&self) -> u32 {
+            self.bet - self.free_bet
+        }
+
+        /// Returns the chips paid to the player for this hand,
+        /// once the house has taken back its free bet from the winnings.
+        #[must_use]
+        pub const fn payout(&self) -> u32 {
+            self.winnings.saturating_sub(self.free_bet)
+        }
+
         /// Returns the number of cards in this hand.
         #[must_use]
         pub fn size(&self) -> usize {
             self.cards.len()
         }
 
+        /// Returns whether this is a split hand of 21 with two cards.
+        /// Unlike a blackjack, this usually only counts as 21.
+        #[must_use]
+        pub fn is_split_21(&self) -> bool {
+            self.from_split && self.size() == 2 && self.value.total == 21
+        }
+
         /// Returns whether this hand is a pair.
         /// A pair is a hand consisting of only two cards with equal rank.
         #[must_use]
         pub fn is_pair(&self) -> bool {
-            self.size() == 2 && self.cards[0].rank == self.cards[1].rank
+            self.is_pair_by(PairDefinition::SameRank)
         }
 
-        /// Calculates the winnings for this hand based on the dealer's hand.
+        /// Returns whether this hand is a pair under the given definition of a pair.
+        #[must_use]
+        pub fn is_pair_by(&self, definition: PairDefinition) -> bool {
+            self.size() == 2
+                && match definition {
+                    PairDefinition::SameRank => self.cards[0].rank == self.cards[1].rank,
+                    PairDefinition::SameValue => self.cards[0].rank.worth() == self.cards[1].rank.worth(),
+                }
+        }
+
+        /// Calculates the winnings for this hand based on the dealer's hand, including any free bet.
+        /// Returns the winnings in whole chips, along with the fraction of a chip which could not
+        /// be paid in whole chips, e.g. for a 3:2 blackjack on an odd bet.
         /// This method should only be called once the dealer's hand is in a terminal state.
         #[must_use]
-        pub fn calculate_winnings(
-            &self,
-            dealer_hand: &DealerHand,
-            blackjack_payout: BlackjackPayout,
-        ) -> u32 {
-            match (&self.status, &dealer_hand.status) {
-                (Status::Surrendered, _) => self.payout_surrender(), // Player surrender
-                (Status::Blackjack, Status::Blackjack) => self.payout_push(), // Blackjack push
-                (Status::Blackjack, _) => self.payout_blackjack(blackjack_payout), // Blackjack win
+        pub fn calculate_winnings(&self, dealer_hand: &DealerHand, rules: &Rules) -> (u32, f64) {
+            let always_wins = rules.player_21_always_wins;
+            let winnings = match (&self.status, &dealer_hand.status) {
+                _ if self.even_money => self.payout_win(), // Even money for a blackjack
+                (Status::Surrendered, _) => return self.payout_surrender(), // Player surrender
+                (Status::Blackjack, Status::Blackjack) if !always_wins => self.payout_push(), // Blackjack push
+                (Status::Blackjack, _) => return self.payout_with_bonus(rules, rules.blackjack_payout.ratio()), // Blackjack win
                 (_, Status::Blackjack) | (Status::Bust, _) => self.payout_loss(), // Dealer blackjack or player bust
-                (_, Status::Bust) => self.payout_win(), // Dealer bust
+                (Status::Charlie, _) => return self.payout_with_bonus(rules, (1, 1)), // Charlie
+                (_, Status::Bust) if rules.dealer_22_pushes && dealer_hand.value.total == 22 => {
+                    self.payout_push() // Dealer 22 pushes
+                }
+                (_, Status::Bust) => return self.payout_with_bonus(rules, (1, 1)), // Dealer bust
                 _ => match self.value.total.cmp(&dealer_hand.value.total) {
-                    Ordering::Greater => self.payout_win(), // Player win
-                    Ordering::Equal => self.payout_push(),  // Push
-                    Ordering::Less => self.payout_loss(),   // Dealer win
+                    Ordering::Greater => return self.payout_with_bonus(rules, (1, 1)), // Player win
+                    Ordering::Equal if always_wins && self.value.total == 21 => {
+                        return self.payout_with_bonus(rules, (1, 1)) // Player 21 always wins
+                    }
+                    Ordering::Equal => self.payout_push(), // Push
+                    Ordering::Less => self.payout_loss(),  // Dealer win
                 },
+            };
+            (winnings, 0.0)
+        }
+
+        /// Returns how the suits of the cards in this hand match.
+        #[must_use]
+        pub fn suits(&self) -> Suits {
+            if self.cards.iter().all(|card| card.suit == Suit::Spades) {
+                Suits::Spades
+            } else if self.cards.iter().all(|card| card.suit == self.cards[0].suit) {
+                Suits::Suited
+            } else {
+                Suits::Mixed
             }
         }
 
-        /// Calculates the winnings for a blackjack win based on whether the game pays 3:2 or 6:5.
-        const fn payout_blackjack(&self, payout: BlackjackPayout) -> u32 {
-            match payout {
-                BlackjackPayout::ThreeToTwo => self.bet + self.bet * 3 / 2,
-                BlackjackPayout::SixToFive => self.bet + self.bet * 6 / 5,
+        /// Returns the special hand this is and the bonus payout it wins, if the rules pay one.
+        /// Bonuses are not paid on doubled hands or on blackjacks taken as even money,
+        /// and only the highest bonus is paid.
+        #[must_use]
+        pub fn bonus(&self, rules: &Rules) -> Option<(Bonus, (u32, u32))> {
+            if self.doubled || self.even_money {
+                return None;
+            }
+            let suits = self.suits();
+            if self.status == Status::Blackjack {
+                let bonus = rules.suited_blackjack_bonus.as_ref()?;
+                return match suits {
+                    Suits::Mixed => None,
+                    Suits::Suited => Some((Bonus::SuitedBlackjack(suits), bonus.suited)),
+                    Suits::Spades => Some((Bonus::SuitedBlackjack(suits), bonus.spades)),
+                };
             }
+            let bonuses = rules.twenty_one_bonuses.as_ref()?;
+            if self.value.total != 21 {
+                return None;
+            }
+            let suited_bonus = |bonus: &SuitedBonus| match suits {
+                Suits::Mixed => bonus.mixed,
+                Suits::Suited => bonus.suited,
+                Suits::Spades => bonus.spades,
+            };
+            let mut worths: Vec<u8> = self.cards.iter().map(|card| card.rank.worth()).collect();
+            worths.sort_unstable();
+            let cards = u8::try_from(self.size()).unwrap_or(u8::MAX);
+            match (cards, worths.as_slice()) {
+                (3, [6, 7, 8]) => Some((Bonus::SixSevenEight(suits), suited_bonus(&bonuses.six_seven_eight))),
+                (3, [7, 7, 7]) => Some((Bonus::SevenSevenSeven(suits), suited_bonus(&bonuses.seven_seven_seven))),
+                (5, _) => Some((Bonus::ManyCard21(cards), bonuses.five_cards)),
+                (6, _) => Some((Bonus::ManyCard21(cards), bonuses.six_cards)),
+                (7.., _) => Some((Bonus::ManyCard21(cards), bonuses.seven_cards)),
+                _ => None,
+            }
+        }
+
+        /// Calculates the winnings for a win paying the given ratio, or the bonus for a special
+        /// hand instead, along with the fraction of a chip which is left over.
+        fn payout_with_bonus(&self, rules: &Rules, ratio: (u32, u32)) -> (u32, f64) {
+            self.payout_ratio(self.bonus(rules).map_or(ratio, |(_, bonus)| bonus))
+        }
+
+        /// Calculates the winnings for a win paying the given ratio (numerator, denominator),
+        /// e.g. for a blackjack, along with the fraction of a chip which is left over.
+        fn payout_ratio(&self, (numerator, denominator): (u32, u32)) -> (u32, f64) {
+            let won = u64::from(self.bet) * u64::from(numerator);
+            let whole = u32::try_from(won / u64::from(denominator)).unwrap_or(u32::MAX);
+            let fraction = (won % u64::from(denominator)) as f64 / f64::from(denominator);
+            (self.bet.saturating_add(whole), fraction)
         }
 
         /// Calculates the winnings for a normal win, which is double the bet.
@@ -389,9 +596,10 @@ pub mod hand {
             self.bet
         }
 
-        /// Calculates the winnings for a surrender, which is half the bet.
-        const fn payout_surrender(&self) -> u32 {
-            self.bet / 2
+        /// Calculates the winnings for a surrender, which is half the bet,
+        /// along with the half chip which is left over from an odd bet.
+        fn payout_surrender(&self) -> (u32, f64) {
+            (self.bet / 2, f64::from(self.bet % 2) / 2.0)
         }
 
         /// Calculates the winnings for a loss, which is 0.
@@ -401,9 +609,9 @@ pub mod hand {
     }
 
     /// All the player's hands in a round of blackjack.
-    /// This always starts with just one hand, but the player might split it into arbitrarily many.
-    /// Split hands are pushed onto the vec.
-    /// The player plays each hand in turn, and the hands are resolved in the order they were split.
+    /// This starts with one hand for each spot the player bet on, but the player might split them
+    /// into arbitrarily many. Split hands are added after the other hands of the same spot.
+    /// The player plays each spot in turn, and the hands of a spot in the order they were split.
     #[derive(Debug, PartialEq, Eq)]
     pub struct PlayerTurn {
         /// The hands in the player's turn, initially just their starting hand.
@@ -424,6 +632,17 @@ pub mod hand {
         }
     }
 
+    /// Converts the hands dealt to each spot into a player turn, starting with the first spot.
+    impl From<Vec<PlayerHand>> for PlayerTurn {
+        fn from(hands: Vec<PlayerHand>) -> Self {
+            debug_assert!(!hands.is_empty(), "a turn needs at least one hand");
+            Self {
+                hands,
+                current_hand_index: 0,
+            }
+        }
+    }
+
     impl PlayerTurn {
         /// Returns a mutable reference to the current hand.
         pub fn current_hand_mut(&mut self) -> &mut PlayerHand {
@@ -438,10 +657,55 @@ pub mod hand {
             self.hands.len() as u8
         }
 
+        /// Returns the number of hands belonging to the spot of the current hand.
+        /// This only grows if the player splits.
+        pub fn hands_in_spot(&self) -> u8 {
+            let spot = self.current_hand().spot;
+            self.hands.iter().filter(|hand| hand.spot == spot).count() as u8
+        }
+
+        /// Returns all hands, e.g. once the round is over.
+        pub fn into_hands(self) -> Vec<PlayerHand> {
+            self.hands
+        }
+
+        /// Returns whether the current hand is one of a pair of split aces.
+        /// All hands of a spot start with cards of the same value, since only pairs can be split.
+        pub fn is_split_ace(&self) -> bool {
+            self.current_hand().from_split && self.current_hand().cards[0].rank == Rank::Ace
+        }
+
         /// Adds a new (split) hand to the player's turn.
-        /// The player may not play this hand immediately, so it is deferred until later.
+        /// The player may not play this hand immediately, so it is deferred until the other hands
+        /// of the same spot have been played.
         pub fn defer(&mut self, hand: PlayerHand) {
-            self.hands.push(hand);
+            let position = self
+                .hands
+                .iter()
+                .rposition(|other| other.spot == hand.spot)
+                .map_or(self.hands.len(), |last| last + 1);
+            self.hands.insert(position, hand);
+        }
+
+        /// Moves on to the next hand, e.g. to offer insurance on each spot before playing.
+        /// Returns false if the current hand is the last one, in which case nothing happens.
+        pub fn next_hand(&mut self) -> bool {
+            if self.current_hand_index + 1 < self.hands.len() {
+                self.current_hand_index += 1;
+                true
+            } else {
+                false
+            }
+        }
+
+        /// Returns whether any hand is still in play.
+        pub fn has_hand_in_play(&self) -> bool {
+            self.hands.iter().any(|hand| hand.status == Status::InPlay)
+        }
+
+        /// Goes back to the first hand.
+        pub fn rewind(&mut self) {
+            self.current_hand_index = 0;
         }
 
         /// Continue playing on the next hand which is still in-play.
@@ -501,10 +765,36 @@ pub mod hand {
 }
 
 pub mod shoe {
-    use rand::thread_rng;
-    use rand_distr::{Distribution, WeightedTreeIndex};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use rand_distr::{Distribution, Normal, WeightedTreeIndex};
+
+    use crate::card::{Card, Rank};
+
+    /// When the discards are returned to the shoe and shuffled.
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub enum ShuffleModel {
+        /// The cut card is placed at the shoe's penetration, and the shoe is shuffled after the
+        /// round in which it is reached
+        #[default]
+        FixedCutCard,
+        /// The cut card is placed at a different position after every shuffle, normally
+        /// distributed around the shoe's penetration with the given standard deviation
+        /// (as a proportion of the shoe). A deviation which is negative or not finite places the
+        /// cut card at the penetration, see [`ShuffleModel::random_cut_card`].
+        RandomCutCard { deviation: f32 },
+        /// A continuous shuffling machine, to which the discards are returned after every round
+        Continuous,
+    }
 
-    use crate::card::Card;
+    impl ShuffleModel {
+        /// A randomly placed cut card with the given standard deviation, or None if the deviation
+        /// is negative or not finite.
+        #[must_use]
+        pub fn random_cut_card(deviation: f32) -> Option<Self> {
+            (deviation.is_finite() && deviation >= 0.0).then_some(Self::RandomCutCard { deviation })
+        }
+    }
 
     /// A shoe is a container that contains multiple decks of cards.
     #[derive(Debug, Clone)]
@@ -515,58 +805,382 @@ pub mod shoe {
         pub cards_drawn: u16,
         /// The proportion of cards to play before shuffling
         pub max_penetration: f32,
+        /// The number of times the shoe has been shuffled
+        pub shuffles: usize,
         /// Weighted distribution to draw random cards from the shoe without replacement.
-        dist: WeightedTreeIndex<u8>,
+        dist: WeightedTreeIndex<u16>,
+        /// The random number generator used to draw cards.
+        /// Two shoes with the same rng state and composition will deal the same cards.
+        rng: StdRng,
+        /// The number of cards of each value (2-11) which have been seen since the last shuffle,
+        /// indexed by value - 2. This excludes the dealer's hole card until it is revealed.
+        seen: [u16; 10],
+        /// The card which was last drawn face down and has not been revealed yet
+        hole_card: Option<Card>,
+        /// When the shoe is shuffled
+        shuffle_model: ShuffleModel,
+        /// The penetration at which the cut card was placed at the last shuffle
+        cut_card: f32,
+        /// The number of cards discarded face down after every shuffle
+        burn_cards: u8,
+        /// The number of copies of each card (by ordinal) in the full shoe
+        composition: [u16; 52],
+        /// The order in which a stacked shoe deals its cards after every shuffle,
+        /// or empty if the cards are drawn at random
+        stack: Vec<Card>,
     }
 
     impl Shoe {
         /// Create a new shoe with the given number of decks and shuffle threshold.
-        /// The shoe is initialized with all cards present.
+        /// The shoe is initialized with all cards present and a randomly seeded rng.
         /// # Panics
         ///
         /// Panics if the number of decks is 0
         #[must_use]
         pub fn new(decks: u8, shuffle_threshold: f32) -> Self {
+            Self::with_rng(decks, shuffle_threshold, StdRng::from_entropy())
+        }
+
+        /// Create a new shoe whose rng is seeded with the given seed.
+        /// Shoes created with the same parameters and seed deal exactly the same sequence of cards,
+        /// which makes simulations reproducible.
+        /// # Panics
+        ///
+        /// Panics if the number of decks is 0
+        #[must_use]
+        pub fn with_seed(decks: u8, shuffle_threshold: f32, seed: u64) -> Self {
+            Self::with_rng(decks, shuffle_threshold, StdRng::seed_from_u64(seed))
+        }
+
+        /// Create a new shoe which draws cards using the given rng.
+        /// # Panics
+        ///
+        /// Panics if the number of decks is 0
+        #[must_use]
+        pub fn with_rng(decks: u8, shuffle_threshold: f32, rng: StdRng) -> Self {
             Self {
                 decks,
                 cards_drawn: 0,
                 max_penetration: shuffle_threshold,
-                dist: WeightedTreeIndex::new([decks; 52]).unwrap(),
+                shuffles: 0,
+                dist: WeightedTreeIndex::new([u16::from(decks); 52]).unwrap(),
+                rng,
+                seen: [0; 10],
+                hole_card: None,
+                shuffle_model: ShuffleModel::FixedCutCard,
+                cut_card: shuffle_threshold,
+                burn_cards: 0,
+                composition: [u16::from(decks); 52],
+                stack: Vec::new(),
+            }
+        }
+
+        /// Create a stacked shoe which deals exactly the given cards in order, e.g. to test what
+        /// happens when the dealer has blackjack. Once the last card has been dealt, the shoe is
+        /// shuffled and deals the same cards in the same order again.
+        /// The shoe counts as a single deck, and is only shuffled once every card has been dealt.
+        /// # Panics
+        ///
+        /// Panics if there are no cards
+        #[must_use]
+        pub fn stacked(cards: Vec<Card>) -> Self {
+            let mut shoe = Self::with_seed(1, 1.0, 0).with_cards(cards.clone());
+            shoe.stack = cards;
+            shoe
+        }
+
+        /// Replaces the cards in the shoe with exactly the given cards, e.g. a stripped deck or a
+        /// shoe with extra aces, and refills the shoe. The cards are drawn at random, and the shoe
+        /// still counts as the same number of decks.
+        /// # Panics
+        ///
+        /// Panics if there are no cards
+        #[must_use]
+        pub fn with_cards(mut self, cards: impl IntoIterator<Item = Card>) -> Self {
+            self.composition = [0; 52];
+            for card in cards {
+                self.composition[card.ordinal()] += 1;
+            }
+            assert!(self.total_cards() > 0, "a shoe needs at least one card");
+            self.stack.clear();
+            self.refill();
+            self
+        }
+
+        /// Removes all cards of the given rank from the shoe, e.g. the tens in Spanish 21,
+        /// and refills the shoe.
+        #[must_use]
+        pub fn without_rank(mut self, rank: &Rank) -> Self {
+            for (ordinal, copies) in self.composition.iter_mut().enumerate() {
+                if Card::from_ordinal(ordinal).rank == *rank {
+                    *copies = 0;
+                }
             }
+            self.stack.retain(|card| card.rank != *rank);
+            self.refill();
+            self
         }
 
-        /// Draws a random card from the shoe.
-        /// The card is removed from the shoe, and the distribution is updated to reflect the new weight.
+        /// The number of cards in the full shoe.
+        #[must_use]
+        pub fn total_cards(&self) -> u16 {
+            self.composition.iter().sum()
+        }
+
+        /// Uses the given model to decide when to shuffle, and refills the shoe.
+        #[must_use]
+        pub fn with_shuffle_model(mut self, shuffle_model: ShuffleModel) -> Self {
+            self.shuffle_model = shuffle_model;
+            self.refill();
+            self
+        }
+
+        /// Burns the given number of cards after every shuffle, and refills the shoe.
+        /// Burnt cards are discarded face down, so they are never seen.
+        #[must_use]
+        pub fn with_burn_cards(mut self, burn_cards: u8) -> Self {
+            self.burn_cards = burn_cards;
+            self.refill();
+            self
+        }
+
+        /// The model used to decide when to shuffle.
+        #[must_use]
+        pub const fn shuffle_model(&self) -> ShuffleModel {
+            self.shuffle_model
+        }
+
+        /// The number of cards burnt after every shuffle.
+        #[must_use]
+        pub const fn burn_cards(&self) -> u8 {
+            self.burn_cards
+        }
+
+        /// Draws a random card from the shoe, face up.
+        /// The card is removed from the shoe and counts as seen.
         /// If the last card is drawn, the shoe is shuffled.
         pub fn draw_card(&mut self) -> Card {
-            let ordinal = self.dist.sample(&mut thread_rng());
+            let card = self.draw();
+            self.seen[usize::from(card.rank.worth() - 2)] += 1;
+            card
+        }
+
+        /// Draws a random card from the shoe, face down.
+        /// The card is removed from the shoe, but does not count as seen until it is revealed.
+        /// If the last card is drawn, the shoe is shuffled.
+        pub fn draw_hole_card(&mut self) -> Card {
+            let card = self.draw();
+            self.hole_card = Some(card.clone());
+            card
+        }
+
+        /// Marks the card which was drawn face down as seen.
+        /// Revealing it again, e.g. to several players at the same table, has no further effect.
+        pub fn reveal_card(&mut self, card: &Card) {
+            if self.hole_card.as_ref() == Some(card) {
+                self.hole_card = None;
+                self.seen[usize::from(card.rank.worth() - 2)] += 1;
+            }
+        }
+
+        /// Removes a random card from the shoe, or the next card from a stacked shoe.
+        fn draw(&mut self) -> Card {
+            let ordinal = match self.stack.get(usize::from(self.cards_drawn)) {
+                Some(card) => card.ordinal(),
+                None => self.dist.sample(&mut self.rng),
+            };
             self.cards_drawn += 1;
             let new_weight = self.dist.get(ordinal) - 1;
             // Update the distribution to reflect the new weight of the removed card
-            if self.dist.update(ordinal, new_weight).is_err() {
-                // The update failed, so we must have drawn the last card
-                debug_assert_eq!(self.cards_drawn, self.decks as u16 * 52, "last card drawn");
+            self.dist.update(ordinal, new_weight).expect("weight decreased by one");
+            if !self.dist.is_valid() {
+                // All weights are zero, so we must have drawn the last card
+                debug_assert_eq!(self.cards_drawn, self.total_cards(), "last card drawn");
                 self.shuffle();
             }
             Card::from_ordinal(ordinal)
         }
 
-        /// Checks if the shoe needs to be shuffled.
+        /// Returns the number of cards with the given value (2-11) which have been seen since the
+        /// last shuffle.
+        #[must_use]
+        pub const fn cards_seen(&self, value: u8) -> u16 {
+            self.seen[value as usize - 2]
+        }
+
+        /// Returns the number of decks left in the shoe, including fractions of a deck.
+        /// Decks from which cards were removed count as whole decks, e.g. 48 cards in Spanish 21.
+        #[must_use]
+        pub fn decks_remaining(&self) -> f32 {
+            let cards_per_deck = f32::from(self.total_cards()) / f32::from(self.decks);
+            f32::from(self.total_cards() - self.cards_drawn) / cards_per_deck
+        }
+
+        /// Returns what a player can see of this shoe.
+        #[must_use]
+        pub const fn view(&self) -> ShoeView<'_> {
+            ShoeView { shoe: self }
+        }
+
+        /// Returns every distinct card and how many copies of it are left in the shoe.
+        pub fn remaining_cards(&self) -> impl Iterator<Item = (Card, u16)> + '_ {
+            (0..52).map(|ordinal| (Card::from_ordinal(ordinal), self.dist.get(ordinal)))
+        }
+
+        /// Checks if the shoe needs to be shuffled at the end of a round.
+        /// A continuous shuffling machine always takes back the discards.
         #[must_use]
         pub fn needs_shuffle(&self) -> bool {
-            let penetration = f32::from(self.cards_drawn) / f32::from(self.decks as u16 * 52);
-            penetration >= self.max_penetration
+            let penetration = f32::from(self.cards_drawn) / f32::from(self.total_cards());
+            match self.shuffle_model {
+                ShuffleModel::FixedCutCard => penetration >= self.max_penetration,
+                ShuffleModel::RandomCutCard { .. } => penetration >= self.cut_card,
+                ShuffleModel::Continuous => true,
+            }
         }
 
         /// Shuffles the shoe.
         /// All cards are returned to the shoe, and the distribution is updated to reflect the new weights.
+        /// The cut card is placed again and the burn cards are discarded.
         ///
         /// # Panics
         ///
         /// Panics if the number of decks is 0
         pub fn shuffle(&mut self) {
+            self.shuffles += 1;
+            self.refill();
+        }
+
+        /// Returns all cards to the shoe, places the cut card and burns cards.
+        /// A hole card which has not been revealed yet belongs to the previous shoe, so it will
+        /// not count as seen.
+        fn refill(&mut self) {
             self.cards_drawn = 0;
-            self.dist = WeightedTreeIndex::new([self.decks; 52]).unwrap();
+            self.seen = [0; 10];
+            self.hole_card = None;
+            self.dist = WeightedTreeIndex::new(self.composition).unwrap();
+            if let ShuffleModel::RandomCutCard { deviation } = self.shuffle_model {
+                let normal = ShuffleModel::random_cut_card(deviation)
+                    .and_then(|_| Normal::new(self.max_penetration, deviation).ok());
+                let cut_card = normal.map_or(self.max_penetration, |normal| normal.sample(&mut self.rng));
+                self.cut_card = cut_card.clamp(0.0, 1.0);
+            }
+            for _ in 0..self.burn_cards {
+                self.draw();
+            }
+        }
+    }
+
+    /// What a player can see of the shoe: the cards which have been seen since the last shuffle,
+    /// but not the cards left in the shoe or the order in which they will be dealt.
+    #[derive(Debug, Clone, Copy)]
+    pub struct ShoeView<'a> {
+        shoe: &'a Shoe,
+    }
+
+    impl ShoeView<'_> {
+        /// The number of decks in the shoe
+        #[must_use]
+        pub const fn decks(&self) -> u8 {
+            self.shoe.decks
+        }
+
+        /// The number of cards that have been drawn from the shoe, face up or down
+        #[must_use]
+        pub const fn cards_drawn(&self) -> u16 {
+            self.shoe.cards_drawn
+        }
+
+        /// The number of times the shoe has been shuffled
+        #[must_use]
+        pub const fn shuffles(&self) -> usize {
+            self.shoe.shuffles
+        }
+
+        /// See [`Shoe::cards_seen`].
+        #[must_use]
+        pub const fn cards_seen(&self, value: u8) -> u16 {
+            self.shoe.cards_seen(value)
+        }
+
+        /// See [`Shoe::decks_remaining`].
+        #[must_use]
+        pub fn decks_remaining(&self) -> f32 {
+            self.shoe.decks_remaining()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::card::Suit;
+        use crate::test_helpers::suited;
+
+        #[test]
+        fn test_seeded_shoes_deal_same_cards() {
+            let mut first = Shoe::with_seed(2, 0.75, 42);
+            let mut second = Shoe::with_seed(2, 0.75, 42);
+            for _ in 0..(2 * 52 * 3) {
+                assert_eq!(first.draw_card(), second.draw_card());
+            }
+        }
+
+        #[test]
+        fn test_custom_and_stacked_shoes() {
+            let aces = (0..4).map(|_| suited(Rank::Ace, Suit::Spades));
+            let mut shoe = Shoe::with_seed(1, 0.75, 9).with_cards(aces.chain([suited(Rank::Two, Suit::Hearts)]));
+            assert_eq!(shoe.total_cards(), 5);
+            let remaining = |shoe: &Shoe, rank: Rank| {
+                shoe.remaining_cards().filter(|(card, _)| card.rank == rank).map(|(_, copies)| copies).sum::<u16>()
+            };
+            assert_eq!((remaining(&shoe, Rank::Ace), remaining(&shoe, Rank::Two), remaining(&shoe, Rank::King)), (4, 1, 0));
+            let dealt: Vec<Card> = (0..5).map(|_| shoe.draw_card()).collect();
+            assert_eq!(dealt.iter().filter(|card| card.rank == Rank::Ace).count(), 4);
+
+            let cards = vec![suited(Rank::Ten, Suit::Clubs), suited(Rank::Ace, Suit::Hearts), suited(Rank::Ten, Suit::Clubs)];
+            shoe = Shoe::stacked(cards.clone());
+            for _ in 0..2 {
+                let dealt: Vec<Card> = (0..3).map(|_| shoe.draw_card()).collect();
+                assert_eq!(dealt, cards);
+            }
+            assert_eq!(shoe.shuffles, 2);
+            let mut without_tens = Shoe::stacked(cards).without_rank(&Rank::Ten);
+            assert_eq!(without_tens.draw_card(), suited(Rank::Ace, Suit::Hearts));
+        }
+
+        #[test]
+        fn test_shuffle_models() {
+            let mut shoe = Shoe::with_seed(6, 0.75, 1).with_shuffle_model(ShuffleModel::Continuous);
+            assert!(shoe.needs_shuffle());
+
+            assert_eq!(ShuffleModel::random_cut_card(-0.05), None);
+            let invalid = ShuffleModel::RandomCutCard { deviation: -0.05 };
+            let invalid = Shoe::with_seed(6, 0.75, 1).with_shuffle_model(invalid);
+            assert!((invalid.cut_card - 0.75).abs() < f32::EPSILON);
+
+            let deviation = 0.05;
+            shoe = Shoe::with_seed(6, 0.75, 1)
+                .with_shuffle_model(ShuffleModel::random_cut_card(deviation).unwrap())
+                .with_burn_cards(1);
+            let mut cut_cards = Vec::new();
+            for _ in 0..100 {
+                assert_eq!(shoe.cards_drawn, 1);
+                assert_eq!((2..=11).map(|value| shoe.cards_seen(value)).sum::<u16>(), 0);
+                while !shoe.needs_shuffle() {
+                    shoe.draw_card();
+                }
+                cut_cards.push(shoe.cards_drawn);
+                shoe.shuffle();
+            }
+            let mean = f32::from(cut_cards.iter().sum::<u16>()) / 100.0 / (6.0 * 52.0);
+            assert!((mean - 0.75).abs() < deviation / 2.0, "mean cut card at {mean}");
+            assert!(cut_cards.iter().any(|&cut_card| cut_card != cut_cards[0]));
+
+            // A hole card drawn before the shuffle is not seen when it is revealed afterwards
+            let hole_card = shoe.draw_hole_card();
+            shoe.shuffle();
+            shoe.reveal_card(&hole_card);
+            assert_eq!((2..=11).map(|value| shoe.cards_seen(value)).sum::<u16>(), 0);
         }
     }
 }
@@ -0,0 +1,273 @@
+//! Side-by-side comparison of several rule variants.
+//! All variants are played against the same cards using common random numbers:
+//! at the start of every round, each variant's table receives an exact copy of the baseline
+//! table's shoe, so the variants only diverge where their rules lead to different play.
+//! This cancels out most of the card noise and makes the measured difference in expected value
+//! far more precise than comparing two independent simulations of the same length.
+
+use std::fmt;
+use std::thread;
+
+use crate::basic_strategy::BasicStrategy;
+use crate::card::shoe::Shoe;
+use crate::game::{Error, Table};
+use crate::rules::Rules;
+use crate::simulation::{play_round, worker_seed, Budget, Progress, PROGRESS_INTERVAL};
+use crate::state::GameState;
+use crate::statistics::{Moments, Statistics};
+use crate::strategy::Strategy;
+
+/// The configuration of a comparison between rule variants.
+/// The first variant is the baseline which all others are compared against.
+/// Every table plays with its own clone of the strategy.
+#[derive(Debug, Clone)]
+pub struct Comparison<S = BasicStrategy> {
+    /// The rule variants to compare, starting with the baseline
+    pub variants: Vec<Rules>,
+    /// The number of decks in the shoe
+    pub decks: u8,
+    /// The proportion of cards to play before shuffling
+    pub penetration: f32,
+    /// The chips each table starts with. A table that runs out of chips is given a fresh bankroll.
+    pub chips: u32,
+    /// The strategy the player follows at every table
+    pub strategy: S,
+    /// How long to run the comparison for, measured at the baseline tables
+    pub budget: Budget,
+    /// The number of worker threads to use
+    pub threads: usize,
+    /// The seed from which the seeds of all shoes are derived.
+    /// If None, the shoes are seeded randomly and the comparison is not reproducible.
+    pub seed: Option<u64>,
+}
+
+impl Default for Comparison<BasicStrategy> {
+    fn default() -> Self {
+        Self {
+            variants: Vec::new(),
+            decks: 6,
+            penetration: 0.75,
+            chips: 1_000_000,
+            strategy: BasicStrategy,
+            budget: Budget::Rounds(1_000_000),
+            threads: thread::available_parallelism().map_or(1, usize::from),
+            seed: None,
+        }
+    }
+}
+
+/// The results of a single rule variant.
+#[derive(Debug, Clone)]
+pub struct VariantResult {
+    /// The rules of this variant
+    pub rules: Rules,
+    /// The merged statistics of all tables playing this variant
+    pub statistics: Statistics,
+    /// The number of times a table ran out of chips and had to be given a fresh bankroll
+    pub bankruptcies: usize,
+}
+
+impl VariantResult {
+    fn new(rules: Rules) -> Self {
+        Self {
+            rules,
+            statistics: Statistics::new(),
+            bankruptcies: 0,
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.statistics.merge(&other.statistics);
+        self.bankruptcies += other.bankruptcies;
+    }
+}
+
+/// The result of a comparison.
+#[derive(Debug, Clone)]
+pub struct ComparisonResult {
+    /// The results of each variant, in the order they were given
+    pub variants: Vec<VariantResult>,
+    /// The per-round difference in net result between each variant and the baseline, in chips.
+    /// The first entry compares the baseline with itself and is always zero.
+    pub differences: Vec<Moments>,
+    /// Whether the comparison was cancelled before the budget was used up
+    pub cancelled: bool,
+}
+
+impl ComparisonResult {
+    fn new(variants: &[Rules]) -> Self {
+        Self {
+            variants: variants.iter().cloned().map(VariantResult::new).collect(),
+            differences: vec![Moments::new(); variants.len()],
+            cancelled: false,
+        }
+    }
+
+    /// Merge the result of another worker into this result.
+    pub fn merge(&mut self, other: &Self) {
+        for (variant, other) in self.variants.iter_mut().zip(&other.variants) {
+            variant.merge(other);
+        }
+        for (difference, other) in self.differences.iter_mut().zip(&other.differences) {
+            difference.merge(other);
+        }
+        self.cancelled |= other.cancelled;
+    }
+}
+
+impl fmt::Display for ComparisonResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (variant, difference)) in self.variants.iter().zip(&self.differences).enumerate() {
+            let round_results = variant.statistics.round_results();
+            let (low, high) = round_results.confidence_interval_95();
+            writeln!(
+                f,
+                "Variant {i}: EV {:.3} Chips per round (95% CI {low:.3} to {high:.3})",
+                round_results.mean(),
+            )?;
+            if i > 0 {
+                let (low, high) = difference.confidence_interval_95();
+                writeln!(
+                    f,
+                    "  vs. baseline: {:+.3} Chips per round (95% CI {low:+.3} to {high:+.3})",
+                    difference.mean(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S> Comparison<S> {
+    /// Returns the same comparison with the player following the given strategy instead.
+    #[must_use]
+    pub fn with_strategy<T>(self, strategy: T) -> Comparison<T> {
+        Comparison {
+            variants: self.variants,
+            decks: self.decks,
+            penetration: self.penetration,
+            chips: self.chips,
+            strategy,
+            budget: self.budget,
+            threads: self.threads,
+            seed: self.seed,
+        }
+    }
+}
+
+impl<S: Strategy + Clone + Send + Sync> Comparison<S> {
+    /// Runs the comparison, blocking until it is finished or cancelled.
+    /// # Errors
+    /// Returns an error if the rules of a variant are invalid, or the strategy gave an input which
+    /// the game could not accept.
+    /// # Panics
+    /// Panics if there are no variants, the number of threads is 0 or a worker thread panics.
+    pub fn run(&self, progress: &Progress) -> Result<ComparisonResult, Error> {
+        assert!(!self.variants.is_empty(), "comparison needs at least one variant");
+        assert!(self.threads > 0, "comparison needs at least one thread");
+        for rules in &self.variants {
+            rules.validate().map_err(Error::RulesError)?;
+        }
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads)
+                .map(|worker| scope.spawn(move || self.run_worker(worker, progress)))
+                .collect();
+            let mut result = ComparisonResult::new(&self.variants);
+            for worker in workers {
+                result.merge(&worker.join().expect("worker thread panicked")?);
+            }
+            Ok(result)
+        })
+    }
+
+    /// Plays rounds at one table per variant until the worker's share of the budget is used up.
+    /// Before every round, all tables are given a copy of the baseline table's shoe.
+    fn run_worker(&self, worker: usize, progress: &Progress) -> Result<ComparisonResult, Error> {
+        let budget = self.budget.share(worker, self.threads);
+        let shoe = Shoe::with_seed(self.decks, self.penetration, worker_seed(self.seed, worker));
+        let mut tables: Vec<Table> = self
+            .variants
+            .iter()
+            .map(|rules| {
+                let mut table = Table::new(self.chips, shoe.clone(), rules.clone());
+                table.fast_forward = true;
+                table
+            })
+            .collect();
+        let mut strategies = vec![self.strategy.clone(); tables.len()];
+        let mut result = ComparisonResult::new(&self.variants);
+        let mut net_results = vec![0; tables.len()];
+        let mut unreported = 0;
+        while !budget.is_exhausted([&tables[0].statistics]) {
+            if progress.is_cancelled() {
+                result.cancelled = true;
+                break;
+            }
+            let shoe = tables[0].shoe.clone();
+            for (i, (table, strategy)) in tables.iter_mut().zip(&mut strategies).enumerate() {
+                if i > 0 {
+                    table.shoe = shoe.clone();
+                }
+                let chips = i64::from(table.chips);
+                let state = play_round(table, strategy)?;
+                net_results[i] = i64::from(table.chips) - chips;
+                if state == GameState::GameOver {
+                    result.variants[i].bankruptcies += 1;
+                    table.chips = self.chips;
+                }
+            }
+            for (difference, net_result) in result.differences.iter_mut().zip(&net_results) {
+                difference.push((net_result - net_results[0]) as f64);
+            }
+            unreported += 1;
+            if unreported == PROGRESS_INTERVAL {
+                progress.report(unreported);
+                unreported = 0;
+            }
+        }
+        progress.report(unreported);
+        for (variant, table) in result.variants.iter_mut().zip(tables) {
+            variant.statistics = table.statistics;
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::DealerSoft17Action;
+
+    #[test]
+    fn test_identical_variants_have_no_difference() {
+        let comparison = Comparison {
+            variants: vec![Rules::default(), Rules::default()],
+            budget: Budget::Rounds(2_000),
+            threads: 2,
+            seed: Some(3),
+            ..Comparison::default()
+        };
+        let result = comparison.run(&Progress::new()).unwrap();
+        assert_eq!(result.differences[1].count(), 2_000);
+        assert!(result.differences[1].mean().abs() < f64::EPSILON);
+        assert!(result.differences[1].variance().abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_hitting_soft_17_favours_the_house() {
+        let comparison = Comparison {
+            variants: vec![
+                Rules::default(),
+                Rules {
+                    dealer_soft_17: DealerSoft17Action::Hit,
+                    ..Rules::default()
+                },
+            ],
+            budget: Budget::Rounds(200_000),
+            seed: Some(17),
+            ..Comparison::default()
+        };
+        let result = comparison.run(&Progress::new()).unwrap();
+        assert!(result.differences[1].mean() < 0.0);
+    }
+}
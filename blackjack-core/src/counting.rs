@@ -0,0 +1,136 @@
+//! Card counting.
+//! A count system assigns a tag to every card value. The running count is the sum of the tags of
+//! all cards seen since the last shuffle, and the true count divides the running count by the
+//! number of decks left in the shoe.
+//! Count systems only describe how to count, the cards seen are tracked by the shoe itself,
+//! so any number of count systems can be applied to the same shoe at the same time.
+
+use std::fmt;
+
+use crate::card::shoe::ShoeView;
+
+/// A card counting system, defined by the tags of each card value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CountSystem {
+    /// The name of the system
+    pub name: &'static str,
+    /// The tag of each card value, indexed by value - 2 (so tens are at index 8 and aces at index 9)
+    pub tags: [f32; 10],
+    /// The running count at the start of a single-deck shoe
+    pub initial_count: f32,
+    /// How much lower the initial running count is for every additional deck.
+    /// This is zero for balanced systems, whose tags sum to zero over a full deck.
+    pub initial_count_per_deck: f32,
+}
+
+impl CountSystem {
+    /// The High-Low system, the most widely used balanced count.
+    pub const HI_LO: Self = Self {
+        name: "Hi-Lo",
+        tags: [1.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0, -1.0, -1.0],
+        initial_count: 0.0,
+        initial_count_per_deck: 0.0,
+    };
+
+    /// The Knock-Out system, an unbalanced count which does not need a true count conversion.
+    /// The running count starts at 4 - 4 × decks, so that it reaches +4 when the shoe favours the player.
+    pub const KO: Self = Self {
+        name: "KO",
+        tags: [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, -1.0, -1.0],
+        initial_count: 0.0,
+        initial_count_per_deck: 4.0,
+    };
+
+    /// The Omega II system, a balanced level 2 count which ignores aces.
+    pub const OMEGA_II: Self = Self {
+        name: "Omega II",
+        tags: [1.0, 1.0, 2.0, 2.0, 2.0, 1.0, 0.0, -1.0, -2.0, 0.0],
+        initial_count: 0.0,
+        initial_count_per_deck: 0.0,
+    };
+
+    /// The Zen count, a balanced level 2 count.
+    pub const ZEN: Self = Self {
+        name: "Zen",
+        tags: [1.0, 1.0, 2.0, 2.0, 2.0, 1.0, 0.0, 0.0, -2.0, -1.0],
+        initial_count: 0.0,
+        initial_count_per_deck: 0.0,
+    };
+
+    /// The Wong Halves system, a balanced level 3 count with fractional tags.
+    pub const WONG_HALVES: Self = Self {
+        name: "Wong Halves",
+        tags: [0.5, 1.0, 1.0, 1.5, 1.0, 0.5, 0.0, -0.5, -1.0, -1.0],
+        initial_count: 0.0,
+        initial_count_per_deck: 0.0,
+    };
+
+    /// All built-in count systems.
+    pub const ALL: [Self; 5] = [
+        Self::HI_LO,
+        Self::KO,
+        Self::OMEGA_II,
+        Self::ZEN,
+        Self::WONG_HALVES,
+    ];
+
+    /// Returns the tag of the given card value (2-11).
+    #[must_use]
+    pub const fn tag(&self, value: u8) -> f32 {
+        self.tags[value as usize - 2]
+    }
+
+    /// Returns whether the tags of a full deck sum to zero.
+    #[must_use]
+    pub fn is_balanced(&self) -> bool {
+        self.initial_count_per_deck == 0.0
+    }
+
+    /// Returns the running count of a freshly shuffled shoe with the given number of decks.
+    #[must_use]
+    pub fn initial_running_count(&self, decks: u8) -> f32 {
+        self.initial_count - self.initial_count_per_deck * f32::from(decks.saturating_sub(1))
+    }
+
+    /// Returns the running count of the cards seen from the given shoe.
+    #[must_use]
+    pub fn running_count(&self, shoe: ShoeView) -> f32 {
+        (2..=11).fold(self.initial_running_count(shoe.decks()), |count, value| {
+            count + self.tag(value) * f32::from(shoe.cards_seen(value))
+        })
+    }
+
+    /// Returns the running count of the given shoe divided by the number of decks left in it.
+    #[must_use]
+    pub fn true_count(&self, shoe: ShoeView) -> f32 {
+        self.running_count(shoe) / shoe.decks_remaining()
+    }
+}
+
+impl Default for CountSystem {
+    fn default() -> Self {
+        Self::HI_LO
+    }
+}
+
+impl fmt::Display for CountSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_deck_counts_back_to_zero() {
+        for system in CountSystem::ALL {
+            let sum: f32 = (2..=11)
+                .map(|value| system.tag(value) * if value == 10 { 16.0 } else { 4.0 })
+                .sum();
+            // An unbalanced count gains exactly what its initial count per deck takes away
+            assert!((sum - system.initial_count_per_deck).abs() < f32::EPSILON, "{system}");
+        }
+    }
+}
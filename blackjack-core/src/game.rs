@@ -6,7 +6,8 @@ use std::fmt;
 
 use crate::card::hand::{DealerHand, PlayerHand, PlayerTurn, Status};
 use crate::card::shoe::Shoe;
-use crate::rules::Rules;
+use crate::rules::{ChipRounding, DoubleRestriction, Rules, RulesError};
+use crate::side_bets::SpotSideBets;
 use crate::state::GameState;
 use crate::statistics::Statistics;
 
@@ -24,10 +25,43 @@ pub enum HandAction {
 #[derive(Debug)]
 pub enum Input {
     Bet(u32),
+    /// Bets on several spots at once, one hand per spot
+    Bets(Vec<u32>),
+    /// Bets on several spots at once, along with side bets on each spot
+    Wagers(Vec<Wager>),
+    /// Sits out the round without betting
+    SitOut,
     Choice(bool),
     Action(HandAction),
 }
 
+/// The bets placed on a single spot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wager {
+    /// The main bet
+    pub bet: u32,
+    /// The bet on each side bet offered at the table, in the order of the rules (0 if not placed)
+    pub side_bets: Vec<u32>,
+}
+
+impl From<u32> for Wager {
+    /// A wager without side bets.
+    fn from(bet: u32) -> Self {
+        Self {
+            bet,
+            side_bets: Vec::new(),
+        }
+    }
+}
+
+impl Wager {
+    /// The total amount bet on the spot, including side bets.
+    #[must_use]
+    pub fn total(&self) -> u32 {
+        self.side_bets.iter().fold(self.bet, |total, &bet| total.saturating_add(bet))
+    }
+}
+
 /// The game table. This is where the game is played.
 /// It holds the player's chips, the shoe, and the game rules.
 #[derive(Debug)]
@@ -36,7 +70,8 @@ pub struct Table {
     pub shoe: Shoe,             // The shoe of cards used in the game
     pub rules: Rules,           // The table rules
     pub statistics: Statistics, // The continuous game statistics
-    pub fast_forward: bool,     // Fast-forward non-user-facing transitions and skip input checks for faster simulation
+    pub fast_forward: bool,     // Fast-forward non-user-facing transitions and skip move checks for faster simulation
+    pub fractional_chips: f64,  // Fractions of a chip owed to the player, paid once they add up to a whole chip
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -44,13 +79,20 @@ pub enum BetError {
     TooLow,
     TooHigh,
     CantAfford,
+    TooManySpots,
+    SideBetNotOffered,
+    SwitchNeedsTwoHands,
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum DoubleError {
     CantAfford,
     NotTwoCards,
+    AlreadyDoubled,
     DoubleAfterSplitNotAllowed,
+    OneCardOnSplitAces,
+    HitSplitAcesNotAllowed,
+    TotalNotAllowed,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -59,6 +101,10 @@ pub enum SplitError {
     NotAPair,
     MaxSplitsReached,
     SplitAcesNotAllowed,
+    ResplitAcesNotAllowed,
+    HitSplitAcesNotAllowed,
+    OneCardOnSplitAces,
+    HitDoubledHandNotAllowed,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -70,6 +116,7 @@ pub enum SurrenderError {
 #[derive(Debug, PartialEq, Eq)]
 pub enum Error {
     WrongInput,
+    RulesError(RulesError),
     BetError(BetError),
     DoubleError(DoubleError),
     SplitError(SplitError),
@@ -80,23 +127,37 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::WrongInput => write!(f, "Wrong input"),
+            Self::RulesError(err) => match err {
+                RulesError::ZeroPayoutDenominator => write!(f, "Payout denominator must not be zero"),
+            },
             Self::BetError(err) => match err {
                 BetError::TooLow => write!(f, "Bet too low"),
                 BetError::TooHigh => write!(f, "Bet too high"),
                 BetError::CantAfford => write!(f, "Can't afford bet"),
+                BetError::TooManySpots => write!(f, "Too many spots"),
+                BetError::SideBetNotOffered => write!(f, "Side bet not offered"),
+                BetError::SwitchNeedsTwoHands => write!(f, "Switch needs two hands with the same bet"),
             },
             Self::DoubleError(err) => match err {
                 DoubleError::CantAfford => write!(f, "Can't afford double down"),
                 DoubleError::NotTwoCards => write!(f, "Not two cards"),
+                DoubleError::AlreadyDoubled => write!(f, "Hand already doubled"),
                 DoubleError::DoubleAfterSplitNotAllowed => {
                     write!(f, "Double after split not allowed")
                 }
+                DoubleError::OneCardOnSplitAces => write!(f, "Split aces receive one card only"),
+                DoubleError::HitSplitAcesNotAllowed => write!(f, "Hit split aces not allowed"),
+                DoubleError::TotalNotAllowed => write!(f, "Double not allowed on this total"),
             },
             Self::SplitError(err) => match err {
                 SplitError::CantAfford => write!(f, "Can't afford split"),
                 SplitError::NotAPair => write!(f, "Not a pair"),
                 SplitError::MaxSplitsReached => write!(f, "Max splits reached"),
                 SplitError::SplitAcesNotAllowed => write!(f, "Split aces not allowed"),
+                SplitError::ResplitAcesNotAllowed => write!(f, "Resplit aces not allowed"),
+                SplitError::HitSplitAcesNotAllowed => write!(f, "Hit split aces not allowed"),
+                SplitError::OneCardOnSplitAces => write!(f, "Split aces receive one card only"),
+                SplitError::HitDoubledHandNotAllowed => write!(f, "Doubled hand can only stand or surrender"),
             },
             Self::SurrenderError(err) => match err {
                 SurrenderError::NotTwoCards => write!(f, "Not two cards"),
@@ -111,14 +172,21 @@ impl fmt::Display for Error {
 pub type ProgressResult = Result<GameState, (GameState, Error)>;
 
 impl Table {
+    /// Creates a table with the given rules.
+    /// # Panics
+    /// Panics if the rules are invalid, see `Rules::validate`.
     #[must_use]
-    pub const fn new(chips: u32, shoe: Shoe, rules: Rules) -> Self {
+    pub fn new(chips: u32, shoe: Shoe, rules: Rules) -> Self {
+        if let Err(err) = rules.validate() {
+            panic!("invalid rules: {}", Error::RulesError(err));
+        }
         Self {
             chips,
             shoe,
             rules,
             statistics: Statistics::new(),
             fast_forward: false,
+            fractional_chips: 0.0,
         }
     }
 
@@ -129,51 +197,74 @@ impl Table {
     #[rustfmt::skip]
     pub fn progress(&mut self, state: GameState, input: Option<Input>) -> ProgressResult {
         match state {
-            GameState::Betting => {
-                if let Some(Input::Bet(bet)) = input {
-                    self.bet(bet)
-                } else {
-                    Err((GameState::Betting, Error::WrongInput))
-                }
-            }
-            GameState::DealFirstPlayerCard { bet } => Ok(self.deal_first_player_card(bet)),
-            GameState::DealFirstDealerCard { player_hand } => {
-                Ok(self.deal_first_dealer_card(player_hand))
+            GameState::Betting => match input {
+                Some(Input::Bet(bet)) => self.bet(vec![bet.into()]),
+                Some(Input::Bets(bets)) => self.bet(bets.into_iter().map(Wager::from).collect()),
+                Some(Input::Wagers(wagers)) => self.bet(wagers),
+                Some(Input::SitOut) => Ok(self.sit_out()),
+                _ => Err((GameState::Betting, Error::WrongInput)),
+            },
+            GameState::DealFirstPlayerCard { wagers } => Ok(self.deal_first_player_card(wagers)),
+            GameState::DealFirstDealerCard { player_hands } => {
+                Ok(self.deal_first_dealer_card(player_hands))
             },
-            GameState::DealSecondPlayerCard { player_hand, dealer_hand } => {
-                Ok(self.deal_second_player_card(player_hand, dealer_hand))
+            GameState::DealSecondPlayerCard { player_hands, dealer_hand } => {
+                Ok(self.deal_second_player_card(player_hands, dealer_hand))
             },
-            GameState::DealHoleCard { player_hand, dealer_hand } => {
-                Ok(self.deal_hole_card(player_hand, dealer_hand))
+            GameState::DealHoleCard { player_hands, dealer_hand } => {
+                Ok(self.deal_hole_card(player_hands, dealer_hand))
             },
-            GameState::OfferEarlySurrender { player_hand, dealer_hand } => {
+            GameState::OfferSwitch { player_hands, dealer_hand } => {
+                if let Some(Input::Choice(switch)) = input {
+                    Ok(self.choose_switch(player_hands, dealer_hand, switch))
+                } else {
+                    Err((GameState::OfferSwitch { player_hands, dealer_hand }, Error::WrongInput))
+                }
+            }
+            GameState::OfferEarlySurrender { player_turn, dealer_hand, insurance_bet } => {
                 if let Some(Input::Choice(early_surrender)) = input {
-                    Ok(self.choose_early_surrender(player_hand, dealer_hand, early_surrender))
+                    Ok(self.choose_early_surrender(player_turn, dealer_hand, insurance_bet, early_surrender))
                 } else {
                     Err((
                         GameState::OfferEarlySurrender {
-                            player_hand,
+                            player_turn,
                             dealer_hand,
+                            insurance_bet,
                         },
                         Error::WrongInput,
                     ))
                 }
             }
-            GameState::OfferInsurance { player_hand, dealer_hand } => {
-                if let Some(Input::Bet(insurance_bet)) = input {
-                    self.bet_insurance(player_hand, dealer_hand, insurance_bet)
+            GameState::OfferInsurance { player_turn, dealer_hand, insurance_bet } => {
+                if let Some(Input::Bet(bet)) = input {
+                    self.bet_insurance(player_turn, dealer_hand, insurance_bet, bet)
                 } else {
                     Err((
                         GameState::OfferInsurance {
-                            player_hand,
+                            player_turn,
                             dealer_hand,
+                            insurance_bet,
                         },
                         Error::WrongInput,
                     ))
                 }
             }
-            GameState::CheckDealerHoleCard { player_hand, dealer_hand, insurance_bet } => {
-                Ok(self.check_dealer_hole_card(player_hand, dealer_hand, insurance_bet))
+            GameState::OfferEvenMoney { player_turn, dealer_hand, insurance_bet } => {
+                if let Some(Input::Choice(even_money)) = input {
+                    Ok(self.choose_even_money(player_turn, dealer_hand, insurance_bet, even_money))
+                } else {
+                    Err((
+                        GameState::OfferEvenMoney {
+                            player_turn,
+                            dealer_hand,
+                            insurance_bet,
+                        },
+                        Error::WrongInput,
+                    ))
+                }
+            }
+            GameState::CheckDealerHoleCard { player_turn, dealer_hand, insurance_bet } => {
+                Ok(self.check_dealer_hole_card(player_turn, dealer_hand, insurance_bet))
             },
             GameState::PlayPlayerTurn { player_turn, dealer_hand, insurance_bet } => {
                 if let Some(Input::Action(action)) = input {
@@ -211,7 +302,7 @@ impl Table {
                 Ok(self.late_surrender(player_turn, dealer_hand, insurance_bet))
             },
             GameState::RevealHoleCard { finished_hands, dealer_hand, insurance_bet } => {
-                Ok(self.play_dealer_turn_or_end_round(finished_hands, dealer_hand, insurance_bet))
+                Ok(self.reveal_dealer_hand(finished_hands, dealer_hand, insurance_bet))
             },
             GameState::PlayDealerTurn { finished_hands, dealer_hand, insurance_bet } => {
                 Ok(self.play_dealer_turn(finished_hands, dealer_hand, insurance_bet))
@@ -227,41 +318,75 @@ impl Table {
         }
     }
 
+    /// A helper function to determine if the player is allowed to hit their current hand.
+    /// The player can always hit, unless the hand is a split ace and the split aces rules
+    /// do not allow it, or the hand was doubled and may only be rescued.
+    /// # Errors
+    /// Returns an error containing the reason why the player cannot hit.
+    pub fn check_hit_allowed(&self, player_turn: &PlayerTurn) -> Result<(), SplitError> {
+        if player_turn.current_hand().doubled {
+            Err(SplitError::HitDoubledHandNotAllowed)
+        } else if !player_turn.is_split_ace() {
+            Ok(())
+        } else if self.rules.split_aces_one_card {
+            Err(SplitError::OneCardOnSplitAces)
+        } else if !self.rules.hit_split_aces {
+            Err(SplitError::HitSplitAcesNotAllowed)
+        } else {
+            Ok(())
+        }
+    }
+
     /// A helper function to determine if the player is allowed to double down on their current hand.
-    /// The player can double down if their hand consists of two cards, they have enough chips,
-    /// and the maximum splits and double after split rules do not prevent them from doing so.
+    /// The player can double down if the double restriction allows their hand, they have enough chips
+    /// (unless the double is free), and the maximum splits, double after split and split aces rules do not prevent them from doing so.
     /// # Errors
     /// Returns an error containing the reason why the player cannot double down.
     pub fn check_double_allowed(&self, player_turn: &PlayerTurn) -> Result<(), DoubleError> {
-        if player_turn.current_hand().size() != 2 {
+        let hand = player_turn.current_hand();
+        let restriction = self.rules.double_restriction;
+        if hand.doubled {
+            Err(DoubleError::AlreadyDoubled)
+        } else if hand.size() != 2 && restriction != DoubleRestriction::AnyCards {
             Err(DoubleError::NotTwoCards)
-        } else if player_turn.current_hand().bet > self.chips {
+        } else if !restriction.allows(hand.size(), hand.value.total, hand.value.soft) {
+            Err(DoubleError::TotalNotAllowed)
+        } else if self.double_cost(hand) > self.chips {
             Err(DoubleError::CantAfford)
-        } else if player_turn.hands() > 1 && !self.rules.double_after_split {
+        } else if hand.from_split && !self.rules.double_after_split {
             Err(DoubleError::DoubleAfterSplitNotAllowed)
+        } else if player_turn.is_split_ace() && self.rules.split_aces_one_card {
+            Err(DoubleError::OneCardOnSplitAces)
+        } else if player_turn.is_split_ace() && !self.rules.hit_split_aces {
+            // Doubling draws a card, so it is only allowed on split aces which may be hit
+            Err(DoubleError::HitSplitAcesNotAllowed)
         } else {
             Ok(())
         }
     }
 
     /// A helper function to determine if the player is allowed to split their current hand.
-    /// The player can split if their hand is a pair, they have enough chips to double their bet,
-    /// and the maximum splits and split-aces rules do not prevent them from doing so.
+    /// The player can split if their hand is a pair, they have enough chips to double their bet
+    /// (unless the split is free), and the pair definition, maximum splits, split aces and resplit aces rules do not prevent them
+    /// from doing so.
     /// # Errors
     /// Returns an error containing the reason why the player cannot split.
     pub fn check_split_allowed(&self, player_turn: &PlayerTurn) -> Result<(), SplitError> {
-        if !player_turn.current_hand().is_pair() {
+        let hand = player_turn.current_hand();
+        if !hand.is_pair_by(self.rules.pair_definition) {
             Err(SplitError::NotAPair)
-        } else if player_turn.current_hand().bet > self.chips {
+        } else if self.split_cost(hand) > self.chips {
             Err(SplitError::CantAfford)
         } else if self
             .rules
-            .max_splits
-            .map_or(false, |max| player_turn.hands() > max)
+            .max_splits_of(&hand.cards[0].rank)
+            .is_some_and(|max| player_turn.hands_in_spot() > max)
         {
             Err(SplitError::MaxSplitsReached)
         } else if player_turn.current_hand().value.soft && !self.rules.split_aces {
             Err(SplitError::SplitAcesNotAllowed)
+        } else if player_turn.is_split_ace() && !self.rules.resplit_aces {
+            Err(SplitError::ResplitAcesNotAllowed)
         } else {
             Ok(())
         }
@@ -269,11 +394,14 @@ impl Table {
 
     /// A helper function to determine if the player is allowed to surrender their current hand.
     /// The player can surrender if their hand consists of two cards and late surrendering
-    /// is enabled in the game configuration.
+    /// is enabled in the game configuration, or if the hand was doubled and double-down rescue
+    /// is allowed.
     /// # Errors
     /// Returns an error containing the reason why the player cannot surrender.
     pub fn check_surrender_allowed(&self, hand: &PlayerHand) -> Result<(), SurrenderError> {
-        if hand.size() != 2 {
+        if hand.doubled && self.rules.double_down_rescue {
+            Ok(())
+        } else if hand.size() != 2 {
             Err(SurrenderError::NotTwoCards)
         } else if !self.rules.late_surrender {
             Err(SurrenderError::LateSurrenderNotAllowed)
@@ -282,213 +410,365 @@ impl Table {
         }
     }
 
-    /// The player places a bet to start the round.
-    /// The bet must be within the table limits and the player must have enough chips.
-    /// If the bet is valid, the game transitions to dealing the first player card.
-    fn bet(&mut self, bet: u32) -> ProgressResult {
-        if self.fast_forward {
-            self.chips -= bet;
-            // Simulated bets should already be valid, so we don't need to check them
-            return Ok(self.deal_first_player_card(bet));
+    /// Returns whether doubling down on the hand is free, i.e. on a hard 9, 10 or 11 with two cards
+    /// if the rules offer free doubles.
+    #[must_use]
+    pub fn is_free_double(&self, hand: &PlayerHand) -> bool {
+        self.rules.free_doubles
+            && DoubleRestriction::NineToEleven.allows(hand.size(), hand.value.total, hand.value.soft)
+    }
+
+    /// Returns whether splitting the pair is free, i.e. any pair other than ten-valued cards
+    /// if the rules offer free splits.
+    #[must_use]
+    pub fn is_free_split(&self, hand: &PlayerHand) -> bool {
+        self.rules.free_splits && hand.cards[0].rank.worth() != 10
+    }
+
+    /// Returns how many chips the player has to pay to double down on the hand.
+    fn double_cost(&self, hand: &PlayerHand) -> u32 {
+        if self.is_free_double(hand) {
+            0
+        } else {
+            hand.bet
         }
-        match (self.rules.min_bet, self.rules.max_bet) {
-            (Some(min), _) if bet < min => {
-                Err((GameState::Betting, Error::BetError(BetError::TooLow)))
-            }
-            (_, Some(max)) if bet > max => {
-                Err((GameState::Betting, Error::BetError(BetError::TooHigh)))
-            }
-            _ if bet > self.chips => {
-                Err((GameState::Betting, Error::BetError(BetError::CantAfford)))
-            }
-            _ => {
-                self.chips -= bet;
-                Ok(GameState::DealFirstPlayerCard { bet })
-            }
+    }
+
+    /// Returns how many chips the player has to pay to split the hand.
+    fn split_cost(&self, hand: &PlayerHand) -> u32 {
+        if self.is_free_split(hand) {
+            0
+        } else {
+            hand.bet
         }
     }
 
-    /// The dealer deals the first card to the player and the player's hand is created.
-    /// Next, the dealer will deal their first card.
-    fn deal_first_player_card(&mut self, bet: u32) -> GameState {
-        let card = self.shoe.draw_card();
-        let player_hand = PlayerHand::new(card, bet);
+    /// The player places a bet on one or more spots to start the round. Spots without a bet are
+    /// left empty, along with any side bets on them, and the other spots keep their positions.
+    /// At least one spot must have a bet. Each bet must be within the table limits,
+    /// the table must have enough spots and offer the side bets,
+    /// and the player must have enough chips for all of them.
+    /// If the player may switch cards, they must bet the same amount on exactly two spots.
+    /// If the bets are valid, the game transitions to dealing the first player cards.
+    fn bet(&mut self, wagers: Vec<Wager>) -> ProgressResult {
+        let placed = || wagers.iter().filter(|wager| wager.bet > 0);
+        if placed().next().is_none() {
+            return Err((GameState::Betting, Error::BetError(BetError::TooLow)));
+        }
+        let total_bet = placed().fold(0, |total: u32, wager| total.saturating_add(wager.total()));
+        let too_low = |wager: &Wager| self.rules.min_bet.is_some_and(|min| wager.bet < min);
+        let too_high = |wager: &Wager| self.rules.max_bet.is_some_and(|max| wager.bet > max);
+        let not_offered = |wager: &Wager| {
+            wager.side_bets.iter().skip(self.rules.side_bets.len()).any(|&bet| bet > 0)
+        };
+        let bets: Vec<u32> = placed().map(|wager| wager.bet).collect();
+        if wagers.iter().skip(usize::from(self.rules.max_spots)).any(|wager| wager.bet > 0) {
+            Err((GameState::Betting, Error::BetError(BetError::TooManySpots)))
+        } else if placed().any(too_low) {
+            Err((GameState::Betting, Error::BetError(BetError::TooLow)))
+        } else if placed().any(too_high) {
+            Err((GameState::Betting, Error::BetError(BetError::TooHigh)))
+        } else if placed().any(not_offered) {
+            Err((GameState::Betting, Error::BetError(BetError::SideBetNotOffered)))
+        } else if self.rules.switch && (bets.len() != 2 || bets[0] != bets[1]) {
+            Err((GameState::Betting, Error::BetError(BetError::SwitchNeedsTwoHands)))
+        } else if total_bet > self.chips {
+            Err((GameState::Betting, Error::BetError(BetError::CantAfford)))
+        } else if self.fast_forward {
+            self.chips -= total_bet;
+            Ok(self.deal_first_player_card(wagers))
+        } else {
+            self.chips -= total_bet;
+            Ok(GameState::DealFirstPlayerCard { wagers })
+        }
+    }
+
+    /// The player sits out this round and watches it being dealt, so the shoe is dealt down just
+    /// as if they had played. Then the next round starts.
+    fn sit_out(&mut self) -> GameState {
+        self.statistics.sit_out();
+        self.deal_dealer_only_round();
+        self.start_next_round()
+    }
+
+    /// Deals a round without any player at the table: the dealer plays out their own hand,
+    /// face up, so every card dealt is seen.
+    pub(crate) fn deal_dealer_only_round(&mut self) {
+        let mut dealer_hand = DealerHand::new(self.shoe.draw_card(), self.rules.dealer_soft_17);
+        while dealer_hand.status == Status::InPlay {
+            dealer_hand += self.shoe.draw_card();
+        }
+    }
+
+    /// The dealer deals the first card to each of the player's spots with a bet, and their hands are
+    /// created. Next, the dealer will deal their first card.
+    fn deal_first_player_card(&mut self, wagers: Vec<Wager>) -> GameState {
+        let player_hands = wagers
+            .into_iter()
+            .enumerate()
+            .filter(|(_, wager)| wager.bet > 0)
+            .map(|(spot, wager)| {
+                let mut hand = PlayerHand::new(self.shoe.draw_card(), wager.bet);
+                hand.spot = spot as u8;
+                if wager.side_bets.iter().any(|&bet| bet > 0) {
+                    hand.side_bets = Some(Box::new(SpotSideBets {
+                        bets: wager.side_bets,
+                        player_cards: Vec::new(),
+                    }));
+                }
+                hand
+            })
+            .collect();
         if self.fast_forward {
-            self.deal_first_dealer_card(player_hand)
+            self.deal_first_dealer_card(player_hands)
         } else {
-            GameState::DealFirstDealerCard { player_hand }
+            GameState::DealFirstDealerCard { player_hands }
         }
     }
 
     /// The dealer deals the first card to themselves and the dealer's hand is created.
-    /// Next, the dealer will deal the second card to the player.
-    fn deal_first_dealer_card(&mut self, player_hand: PlayerHand) -> GameState {
+    /// Next, the dealer will deal the second card to each of the player's spots.
+    fn deal_first_dealer_card(&mut self, player_hands: Vec<PlayerHand>) -> GameState {
         let card = self.shoe.draw_card();
         let dealer_hand = DealerHand::new(card, self.rules.dealer_soft_17);
         if self.fast_forward {
-            self.deal_second_player_card(player_hand, dealer_hand)
+            self.deal_second_player_card(player_hands, dealer_hand)
         } else {
             GameState::DealSecondPlayerCard {
-                player_hand,
+                player_hands,
                 dealer_hand,
             }
         }
     }
 
-    /// The dealer deals the second card to the player.
+    /// The dealer deals the second card to each of the player's spots.
     /// Next, the dealer will deal the second card to themselves, also known as the hole card.
     fn deal_second_player_card(
         &mut self,
-        mut player_hand: PlayerHand,
+        mut player_hands: Vec<PlayerHand>,
         dealer_hand: DealerHand,
     ) -> GameState {
-        player_hand += self.shoe.draw_card();
+        for hand in &mut player_hands {
+            *hand += self.shoe.draw_card();
+            if let Some(side_bets) = &mut hand.side_bets {
+                side_bets.player_cards.clone_from(&hand.cards);
+            }
+        }
         if self.fast_forward {
-            self.deal_hole_card(player_hand, dealer_hand)
+            self.deal_hole_card(player_hands, dealer_hand)
         } else {
             GameState::DealHoleCard {
-                player_hand,
+                player_hands,
                 dealer_hand,
             }
         }
     }
 
-    /// The dealer deals the hole card to themselves.
-    /// If the dealer is showing a 10 or an Ace, they will check their hole card for Blackjack.
-    /// If early surrender or insurance is offered, the game will transition to those states first.
-    /// If the dealer cannot have Blackjack or if the player does have Blackjack, the dealer will
-    /// not check their hole card, and will instead let the player play their hand.
+    /// The dealer deals the hole card to themselves, unless playing without a hole card.
     fn deal_hole_card(
         &mut self,
-        player_hand: PlayerHand,
+        player_hands: Vec<PlayerHand>,
         mut dealer_hand: DealerHand,
     ) -> GameState {
-        dealer_hand += self.shoe.draw_card();
-        if dealer_hand.showing() < 10 || player_hand.status == Status::Blackjack {
-            self.play_player_turn_or_go_to_dealer_turn(player_hand.into(), dealer_hand, 0)
-        } else if self.rules.early_surrender {
+        if !self.rules.no_hole_card {
+            dealer_hand += self.shoe.draw_hole_card();
+        }
+        self.start_player_turn(player_hands, dealer_hand)
+    }
+
+    /// All cards have been dealt, so the player can start their turn.
+    /// If the player may switch cards and plays two hands, they decide whether to switch first.
+    /// Then, the player's options before the dealer checks for Blackjack are offered on each spot.
+    pub(crate) fn start_player_turn(
+        &mut self,
+        player_hands: Vec<PlayerHand>,
+        dealer_hand: DealerHand,
+    ) -> GameState {
+        if self.rules.switch && player_hands.len() == 2 {
+            GameState::OfferSwitch {
+                player_hands,
+                dealer_hand,
+            }
+        } else {
+            self.offer_options(player_hands.into(), dealer_hand, 0)
+        }
+    }
+
+    /// The player decides whether to switch the second cards of their two hands.
+    /// Then, the player's options are offered on each spot.
+    fn choose_switch(
+        &mut self,
+        mut player_hands: Vec<PlayerHand>,
+        dealer_hand: DealerHand,
+        switch: bool,
+    ) -> GameState {
+        if let ([first], [second]) = player_hands.split_at_mut(1) {
+            if switch {
+                first.switch_second_card(second);
+            }
+        }
+        self.offer_options(player_hands.into(), dealer_hand, 0)
+    }
+
+    /// If the dealer is showing a 10 or an Ace, early surrender and insurance may be offered on
+    /// the current spot, unless it has Blackjack. A spot with Blackjack may be offered even money
+    /// instead if the dealer is showing an Ace. The spots are offered their options in turn.
+    fn offer_options(
+        &mut self,
+        player_turn: PlayerTurn,
+        dealer_hand: DealerHand,
+        insurance_bet: u32,
+    ) -> GameState {
+        let blackjack = player_turn.current_hand().status == Status::Blackjack;
+        let showing = dealer_hand.showing();
+        if blackjack && self.rules.even_money && showing == 11 {
+            GameState::OfferEvenMoney {
+                player_turn,
+                dealer_hand,
+                insurance_bet,
+            }
+        } else if !blackjack && self.rules.early_surrender && showing >= 10 {
             GameState::OfferEarlySurrender {
-                player_hand,
+                player_turn,
                 dealer_hand,
+                insurance_bet,
             }
-        } else if self.rules.insurance && dealer_hand.showing() == 11 {
+        } else if !blackjack && self.rules.insurance && showing == 11 {
             GameState::OfferInsurance {
-                player_hand,
+                player_turn,
                 dealer_hand,
+                insurance_bet,
             }
-        } else if self.fast_forward {
-            self.check_dealer_hole_card(player_hand, dealer_hand, 0)
+        } else {
+            self.offer_options_on_next_spot(player_turn, dealer_hand, insurance_bet)
+        }
+    }
+
+    /// Moves on to offer the player's options on the next spot.
+    /// Once all spots have had their options, the dealer checks their hole card for Blackjack.
+    /// If the dealer cannot have Blackjack or if no spot is still in play, the dealer will
+    /// not check their hole card, and will instead let the player play their hands.
+    fn offer_options_on_next_spot(
+        &mut self,
+        mut player_turn: PlayerTurn,
+        dealer_hand: DealerHand,
+        insurance_bet: u32,
+    ) -> GameState {
+        if player_turn.next_hand() {
+            return self.offer_options(player_turn, dealer_hand, insurance_bet);
+        }
+        player_turn.rewind();
+        if dealer_hand.showing() < 10 || !player_turn.has_hand_in_play() {
+            self.play_player_turn_or_go_to_dealer_turn(player_turn, dealer_hand, insurance_bet)
+        } else if self.fast_forward || !self.rules.dealer_peeks(dealer_hand.showing()) {
+            self.check_dealer_hole_card(player_turn, dealer_hand, insurance_bet)
         } else {
             GameState::CheckDealerHoleCard {
-                player_hand,
+                player_turn,
                 dealer_hand,
-                insurance_bet: 0,
+                insurance_bet,
             }
         }
     }
 
-    /// The player decides whether to surrender early.
-    /// If the player surrenders, their hand is finished and the round is over.
+    /// The player decides whether to surrender the current spot early.
+    /// If the player surrenders, the spot's hand is finished.
     /// Otherwise, if insurance is offered and the dealer is showing an Ace, the player can place
-    /// an insurance bet.
-    /// Otherwise, the dealer checks their hole card for Blackjack.
+    /// an insurance bet on the spot.
+    /// Then, the player's options are offered on the next spot.
     fn choose_early_surrender(
         &mut self,
-        player_hand: PlayerHand,
+        mut player_turn: PlayerTurn,
         dealer_hand: DealerHand,
+        insurance_bet: u32,
         surrender: bool,
     ) -> GameState {
         if surrender {
-            if self.fast_forward {
-                self.late_surrender(player_hand.into(), dealer_hand, 0)
-            } else {
-                GameState::PlayerSurrender {
-                    player_turn: player_hand.into(),
-                    dealer_hand,
-                    insurance_bet: 0,
-                }
-            }
+            player_turn.current_hand_mut().surrender();
+            self.offer_options_on_next_spot(player_turn, dealer_hand, insurance_bet)
         } else if self.rules.insurance && dealer_hand.showing() == 11 {
             GameState::OfferInsurance {
-                player_hand,
+                player_turn,
                 dealer_hand,
+                insurance_bet,
             }
-        } else if self.fast_forward {
-            self.check_dealer_hole_card(player_hand, dealer_hand, 0)
         } else {
-            GameState::CheckDealerHoleCard {
-                player_hand,
-                dealer_hand,
-                insurance_bet: 0,
-            }
+            self.offer_options_on_next_spot(player_turn, dealer_hand, insurance_bet)
         }
     }
 
-    /// The player places an insurance bet.
-    /// The bet must be less than half of the player's original bet,
+    /// The player decides whether to take even money for the Blackjack on the current spot.
+    /// If so, the hand is paid 1:1 whether or not the dealer has Blackjack.
+    /// Otherwise, the player keeps their Blackjack, which pushes if the dealer has Blackjack too.
+    /// Even money is paid without revealing the hole card, so it is only seen at the end of the round.
+    /// Then, the player's options are offered on the next spot.
+    fn choose_even_money(
+        &mut self,
+        mut player_turn: PlayerTurn,
+        dealer_hand: DealerHand,
+        insurance_bet: u32,
+        even_money: bool,
+    ) -> GameState {
+        player_turn.current_hand_mut().even_money = even_money;
+        self.offer_options_on_next_spot(player_turn, dealer_hand, insurance_bet)
+    }
+
+    /// The player places an insurance bet on the current spot.
+    /// The bet must be less than half of the spot's original bet,
     /// and the player must have enough chips.
-    /// Next, the dealer will check their hole card for Blackjack.
+    /// Then, the player's options are offered on the next spot.
     fn bet_insurance(
         &mut self,
-        player_hand: PlayerHand,
+        player_turn: PlayerTurn,
         dealer_hand: DealerHand,
         insurance_bet: u32,
+        bet: u32,
     ) -> ProgressResult {
-        if self.fast_forward {
-            // Simulated bets should already be valid, so we don't need to check them
-            self.chips -= insurance_bet;
-            Ok(self.check_dealer_hole_card(player_hand, dealer_hand, insurance_bet))
-        } else if insurance_bet > player_hand.bet / 2 {
-            Err((
-                GameState::OfferInsurance {
-                    player_hand,
-                    dealer_hand,
-                },
-                Error::BetError(BetError::TooHigh),
-            ))
-        } else if insurance_bet > self.chips {
-            Err((
+        let error = if bet > player_turn.current_hand().bet / 2 {
+            Some(BetError::TooHigh)
+        } else if bet > self.chips {
+            Some(BetError::CantAfford)
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            return Err((
                 GameState::OfferInsurance {
-                    player_hand,
+                    player_turn,
                     dealer_hand,
+                    insurance_bet,
                 },
-                Error::BetError(BetError::CantAfford),
-            ))
-        } else {
-            self.chips -= insurance_bet;
-            Ok(GameState::CheckDealerHoleCard {
-                player_hand,
-                dealer_hand,
-                insurance_bet,
-            })
+                Error::BetError(error),
+            ));
         }
+        self.chips -= bet;
+        Ok(self.offer_options_on_next_spot(player_turn, dealer_hand, insurance_bet + bet))
     }
 
-    /// The dealer checks their hole card for Blackjack.
-    /// If the dealer does not have Blackjack, it is the player's turn to play their hand.
+    /// The dealer checks their hole card for Blackjack, if the peek rules say so.
+    /// If the dealer does not have Blackjack, it is the player's turn to play their hands.
     /// If the dealer does have Blackjack, the round is over.
+    /// If the dealer does not peek, the player plays their hands without knowing.
     fn check_dealer_hole_card(
         &mut self,
-        player_hand: PlayerHand,
+        player_turn: PlayerTurn,
         dealer_hand: DealerHand,
         insurance_bet: u32,
     ) -> GameState {
-        if dealer_hand.status == Status::Blackjack {
+        if dealer_hand.status == Status::Blackjack && self.rules.dealer_peeks(dealer_hand.showing()) {
+            self.reveal_hole_card(&dealer_hand);
+            let finished_hands = player_turn.into_hands();
             if self.fast_forward {
-                self.end_round(vec![player_hand], dealer_hand, insurance_bet)
+                self.end_round(finished_hands, dealer_hand, insurance_bet)
             } else {
                 GameState::RoundOver {
-                    finished_hands: vec![player_hand],
+                    finished_hands,
                     dealer_hand,
                     insurance_bet,
                 }
             }
         } else {
-            self.play_player_turn_or_go_to_dealer_turn(
-                player_hand.into(),
-                dealer_hand,
-                insurance_bet,
-            )
+            self.play_player_turn_or_go_to_dealer_turn(player_turn, dealer_hand, insurance_bet)
         }
     }
 
@@ -507,11 +787,24 @@ impl Table {
             HandAction::Hit if self.fast_forward => {
                 Ok(self.hit(player_turn, dealer_hand, insurance_bet))
             }
-            HandAction::Hit => Ok(GameState::PlayerHit {
-                player_turn,
-                dealer_hand,
-                insurance_bet,
-            }),
+            HandAction::Hit => {
+                if let Err(err) = self.check_hit_allowed(&player_turn) {
+                    Err((
+                        GameState::PlayPlayerTurn {
+                            player_turn,
+                            dealer_hand,
+                            insurance_bet,
+                        },
+                        Error::SplitError(err),
+                    ))
+                } else {
+                    Ok(GameState::PlayerHit {
+                        player_turn,
+                        dealer_hand,
+                        insurance_bet,
+                    })
+                }
+            }
             HandAction::Stand if self.fast_forward => {
                 Ok(self.stand(player_turn, dealer_hand, insurance_bet))
             }
@@ -522,7 +815,7 @@ impl Table {
             }),
             HandAction::Double if self.fast_forward => {
                 // Simulated moves should already be valid, so we don't need to check them
-                self.chips -= player_turn.current_hand().bet;
+                self.chips -= self.double_cost(player_turn.current_hand());
                 Ok(self.double(player_turn, dealer_hand, insurance_bet))
             }
             HandAction::Double => {
@@ -536,7 +829,7 @@ impl Table {
                         Error::DoubleError(err),
                     ))
                 } else {
-                    self.chips -= player_turn.current_hand().bet;
+                    self.chips -= self.double_cost(player_turn.current_hand());
                     Ok(GameState::PlayerDouble {
                         player_turn,
                         dealer_hand,
@@ -546,7 +839,7 @@ impl Table {
             }
             HandAction::Split if self.fast_forward => {
                 // Simulated moves should already be valid, so we don't need to check them
-                self.chips -= player_turn.current_hand().bet;
+                self.chips -= self.split_cost(player_turn.current_hand());
                 Ok(self.split(player_turn, dealer_hand, insurance_bet))
             }
             HandAction::Split => {
@@ -560,7 +853,7 @@ impl Table {
                         Error::SplitError(err),
                     ))
                 } else {
-                    self.chips -= player_turn.current_hand().bet;
+                    self.chips -= self.split_cost(player_turn.current_hand());
                     Ok(GameState::PlayerSplit {
                         player_turn,
                         dealer_hand,
@@ -602,6 +895,7 @@ impl Table {
         insurance_bet: u32,
     ) -> GameState {
         *player_turn.current_hand_mut() += self.shoe.draw_card();
+        self.check_charlie(player_turn.current_hand_mut());
         self.play_player_turn_or_go_to_dealer_turn(player_turn, dealer_hand, insurance_bet)
     }
 
@@ -617,7 +911,7 @@ impl Table {
         self.play_player_turn_or_go_to_dealer_turn(player_turn, dealer_hand, insurance_bet)
     }
 
-    /// The player doubles down and the hand is finished.
+    /// The player doubles down and the hand is finished. If the double is free, the house puts it up.
     /// We continue the player's turn if they still have hands in play, or go to the dealer's turn.
     fn double(
         &mut self,
@@ -625,11 +919,22 @@ impl Table {
         dealer_hand: DealerHand,
         insurance_bet: u32,
     ) -> GameState {
-        player_turn.current_hand_mut().double(self.shoe.draw_card());
+        if self.is_free_double(player_turn.current_hand()) {
+            let hand = player_turn.current_hand_mut();
+            hand.free_bet += hand.bet;
+        }
+        let hand = player_turn.current_hand_mut();
+        if self.rules.double_down_rescue {
+            hand.double_with_rescue(self.shoe.draw_card());
+        } else {
+            hand.double(self.shoe.draw_card());
+        }
+        self.check_charlie(player_turn.current_hand_mut());
         self.play_player_turn_or_go_to_dealer_turn(player_turn, dealer_hand, insurance_bet)
     }
 
-    /// The dealer separates the player's hand into two.
+    /// The dealer separates the player's hand into two. If the split is free, the house puts up the
+    /// bet on the new hand.
     /// Next, the dealer will deal a new card to the first of the two split hands.
     fn split(
         &mut self,
@@ -637,7 +942,11 @@ impl Table {
         dealer_hand: DealerHand,
         insurance_bet: u32,
     ) -> GameState {
-        let new_hand = player_turn.current_hand_mut().split();
+        let free = self.is_free_split(player_turn.current_hand());
+        let mut new_hand = Box::new(player_turn.current_hand_mut().split());
+        if free {
+            new_hand.free_bet = new_hand.bet;
+        }
         if self.fast_forward {
             self.deal_first_split_card(player_turn, new_hand, dealer_hand, insurance_bet)
         } else {
@@ -655,11 +964,11 @@ impl Table {
     fn deal_first_split_card(
         &mut self,
         mut player_turn: PlayerTurn,
-        new_hand: PlayerHand,
+        new_hand: Box<PlayerHand>,
         dealer_hand: DealerHand,
         insurance_bet: u32,
     ) -> GameState {
-        *player_turn.current_hand_mut() += self.shoe.draw_card();
+        self.deal_split_card(player_turn.current_hand_mut());
         if self.fast_forward {
             self.deal_second_split_card(player_turn, new_hand, dealer_hand, insurance_bet)
         } else {
@@ -674,19 +983,38 @@ impl Table {
 
     /// The dealer deals a card to the second of the two split hands.
     /// The player will play the first split hand first, and the second split hand after.
+    /// If split aces receive one card only, they stand unless they can be resplit.
     /// We continue the player's turn if they still have hands in play, or go to the dealer's turn.
     fn deal_second_split_card(
         &mut self,
         mut player_turn: PlayerTurn,
-        mut new_hand: PlayerHand,
+        mut new_hand: Box<PlayerHand>,
         dealer_hand: DealerHand,
         insurance_bet: u32,
     ) -> GameState {
-        new_hand += self.shoe.draw_card();
-        player_turn.defer(new_hand);
+        self.deal_split_card(&mut new_hand);
+        player_turn.defer(*new_hand);
         self.play_player_turn_or_go_to_dealer_turn(player_turn, dealer_hand, insurance_bet)
     }
 
+    /// The dealer deals a card to a split hand.
+    /// A split hand of 21 is not a blackjack, unless the rules say otherwise.
+    fn deal_split_card(&mut self, hand: &mut PlayerHand) {
+        *hand += self.shoe.draw_card();
+        if self.rules.split_21_is_blackjack && hand.is_split_21() {
+            hand.status = Status::Blackjack;
+        }
+    }
+
+    /// A hand which reaches the number of cards of a Charlie without busting wins automatically.
+    fn check_charlie(&self, hand: &mut PlayerHand) {
+        if self.rules.charlie.is_some_and(|cards| hand.size() >= usize::from(cards))
+            && matches!(hand.status, Status::InPlay | Status::Stood)
+        {
+            hand.status = Status::Charlie;
+        }
+    }
+
     /// The player surrenders and the hand is finished.
     /// We continue the player's turn if they still have hands in play, or go to the dealer's turn.
     fn late_surrender(
@@ -705,27 +1033,27 @@ impl Table {
     fn play_player_turn_or_go_to_dealer_turn(
         &mut self,
         player_turn: PlayerTurn,
-        mut dealer_hand: DealerHand,
+        dealer_hand: DealerHand,
         insurance_bet: u32,
     ) -> GameState {
         match player_turn.continue_playing() {
+            Ok(mut player_turn)
+                if self.rules.split_aces_one_card
+                    && player_turn.is_split_ace()
+                    && self.check_split_allowed(&player_turn).is_err() =>
+            {
+                // Split aces which received their one card are finished, unless they can be resplit
+                player_turn.current_hand_mut().stand();
+                self.play_player_turn_or_go_to_dealer_turn(player_turn, dealer_hand, insurance_bet)
+            }
             Ok(player_turn) => GameState::PlayPlayerTurn {
                 player_turn,
                 dealer_hand,
                 insurance_bet,
             },
             Err(finished_hands) => {
-                // If the player did not explicitly stand on any of their hands,
-                // the dealer will simply flip their hole card and stand immediately.
-                if dealer_hand.status == Status::InPlay
-                    && !finished_hands
-                        .iter()
-                        .any(|hand| hand.status == Status::Stood)
-                {
-                    dealer_hand.status = Status::Stood;
-                }
                 if self.fast_forward {
-                    self.play_dealer_turn_or_end_round(finished_hands, dealer_hand, insurance_bet)
+                    self.reveal_dealer_hand(finished_hands, dealer_hand, insurance_bet)
                 } else {
                     GameState::RevealHoleCard {
                         finished_hands,
@@ -739,6 +1067,65 @@ impl Table {
 
     /// The dealer reveals their hole card.
     /// If the dealer's hand is no longer in play, the round is over.
+    fn reveal_dealer_hand(
+        &mut self,
+        finished_hands: Vec<PlayerHand>,
+        mut dealer_hand: DealerHand,
+        insurance_bet: u32,
+    ) -> GameState {
+        self.turn_over_hole_card(&mut dealer_hand, &finished_hands, insurance_bet > 0);
+        self.play_dealer_turn_or_end_round(finished_hands, dealer_hand, insurance_bet)
+    }
+
+    /// The dealer turns over their hole card after all players have finished their hands.
+    /// If no player explicitly stood on any of their hands and no side bet depends on the dealer's
+    /// final hand, the dealer will simply flip their hole card and stand immediately.
+    pub(crate) fn turn_over_hole_card<'a>(
+        &mut self,
+        dealer_hand: &mut DealerHand,
+        finished_hands: impl IntoIterator<Item = &'a PlayerHand>,
+        insured: bool,
+    ) {
+        self.reveal_hole_card(dealer_hand);
+        let (stood, needs_second_card) = finished_hands.into_iter().fold(
+            (false, insured),
+            |(stood, needs_second_card), hand| {
+                (
+                    stood || hand.status == Status::Stood || self.needs_dealer_hand(hand),
+                    needs_second_card
+                        || (matches!(hand.status, Status::Blackjack | Status::Surrendered | Status::Charlie)
+                            && !hand.even_money),
+                )
+            },
+        );
+        if dealer_hand.status == Status::InPlay && !stood {
+            // Without a hole card, the dealer still needs their second card to settle
+            // a player blackjack (unless paid even money), a surrender, a Charlie or an insurance bet
+            if self.rules.no_hole_card && needs_second_card {
+                *dealer_hand += self.shoe.draw_card();
+            }
+            if dealer_hand.status == Status::InPlay {
+                dealer_hand.status = Status::Stood;
+            }
+        }
+    }
+
+    /// Returns whether a side bet on the hand's spot depends on the dealer's final hand.
+    fn needs_dealer_hand(&self, hand: &PlayerHand) -> bool {
+        hand.side_bets.iter().flat_map(|side_bets| side_bets.placed()).any(|(side_bet, _)| {
+            self.rules.side_bets[side_bet].needs_dealer_hand()
+        })
+    }
+
+    /// The dealer turns over their hole card, so it is seen by the player from now on.
+    fn reveal_hole_card(&mut self, dealer_hand: &DealerHand) {
+        if let Some(card) = dealer_hand.hole_card() {
+            self.shoe.reveal_card(card);
+        }
+    }
+
+    /// If the dealer's hand is still in play, they draw another card.
+    /// Otherwise, the round is over.
     fn play_dealer_turn_or_end_round(
         &mut self,
         finished_hands: Vec<PlayerHand>,
@@ -781,7 +1168,7 @@ impl Table {
         self.play_dealer_turn_or_end_round(finished_hands, dealer_hand, insurance_bet)
     }
 
-    /// The round is over.
+    /// The round is over, and all of the dealer's cards are seen.
     /// The player's total bet and winnings are calculated.
     fn end_round(
         &mut self,
@@ -789,15 +1176,41 @@ impl Table {
         dealer_hand: DealerHand,
         insurance: u32,
     ) -> GameState {
-        let total_bet = finished_hands.iter().map(|hand| hand.bet).sum::<u32>() + insurance;
+        self.reveal_hole_card(&dealer_hand);
+        let side_bets = finished_hands
+            .iter()
+            .filter_map(|hand| hand.side_bets.as_ref().map(|side_bets| side_bets.total()))
+            .sum::<u32>();
+        let total_bet = finished_hands.iter().map(PlayerHand::paid_bet).sum::<u32>() + insurance + side_bets;
+        let late_dealer_blackjack = dealer_hand.status == Status::Blackjack
+            && !self.rules.dealer_peeks(dealer_hand.showing());
         for hand in &mut finished_hands {
-            hand.winnings = hand.calculate_winnings(&dealer_hand, self.rules.blackjack_payout);
+            let (winnings, fraction) = hand.calculate_winnings(&dealer_hand, &self.rules);
+            hand.winnings = winnings;
+            // Bonuses are only paid on hands which win
+            hand.paid_bonus = hand
+                .bonus(&self.rules)
+                .filter(|_| winnings > hand.bet)
+                .map(|(bonus, _)| bonus);
+            // A surrendered hand loses everything to a late dealer blackjack, see below
+            if !(late_dealer_blackjack && hand.status == Status::Surrendered) {
+                hand.winnings += self.collect_fraction(fraction);
+            }
         }
-        let mut total_winnings = finished_hands.iter().map(|hand| hand.winnings).sum();
-        if dealer_hand.status == Status::Blackjack {
-            total_winnings += insurance * 2;
+        if late_dealer_blackjack {
+            self.settle_late_dealer_blackjack(&mut finished_hands);
         }
-        self.statistics.update(finished_hands, dealer_hand);
+        // Insurance pays 2:1, so the player gets back their insurance bet plus twice its amount
+        let insurance_winnings = if dealer_hand.status == Status::Blackjack {
+            insurance * 3
+        } else {
+            0
+        };
+        let side_bet_winnings = self.settle_side_bets(&finished_hands, &dealer_hand);
+        let total_winnings = finished_hands.iter().map(PlayerHand::payout).sum::<u32>()
+            + insurance_winnings
+            + side_bet_winnings;
+        self.statistics.update(finished_hands, dealer_hand, insurance, insurance_winnings);
         if self.fast_forward {
             self.pay_out_winnings(total_winnings)
         } else {
@@ -808,28 +1221,91 @@ impl Table {
         }
     }
 
+    /// Settles the side bets on each spot against the player's first two cards and the dealer's
+    /// final hand. Returns the total paid on side bets, including the bets which are returned.
+    fn settle_side_bets(&mut self, finished_hands: &[PlayerHand], dealer_hand: &DealerHand) -> u32 {
+        let mut total_winnings = 0;
+        for side_bets in finished_hands.iter().filter_map(|hand| hand.side_bets.as_ref()) {
+            for (index, bet) in side_bets.placed() {
+                let side_bet = &self.rules.side_bets[index];
+                let name = side_bet.name();
+                let winnings = match side_bet.payout(&side_bets.player_cards, dealer_hand) {
+                    Some(odds) => {
+                        let winnings = f64::from(bet) * (odds + 1.0);
+                        winnings.floor() as u32 + self.collect_fraction(winnings.fract())
+                    }
+                    None => 0,
+                };
+                self.statistics.update_side_bet(index, name, bet, winnings);
+                total_winnings += winnings;
+            }
+        }
+        total_winnings
+    }
+
+    /// Keeps the fraction of a chip which could not be paid, as far as the rounding rule allows.
+    /// Returns the whole chips to pay once the kept fractions add up to at least one chip.
+    fn collect_fraction(&mut self, fraction: f64) -> u32 {
+        self.fractional_chips += match self.rules.chip_rounding {
+            ChipRounding::Down => return 0,
+            ChipRounding::HalfChips => (fraction * 2.0).floor() / 2.0,
+            ChipRounding::Exact => fraction,
+        };
+        // Fractions such as fifths are not exact in floating point, so allow for a tiny error
+        let whole = (self.fractional_chips + 1e-9).floor();
+        self.fractional_chips = (self.fractional_chips - whole).max(0.0);
+        whole as u32
+    }
+
+    /// If the dealer did not peek, their blackjack is only revealed after the player has played,
+    /// so the player loses everything they bet on their hands, including doubles and splits.
+    /// Surrendering does not save half the bet either, since the dealer never checked for blackjack.
+    /// If only original bets are lost, the bets added by doubling and splitting are returned.
+    fn settle_late_dealer_blackjack(&self, finished_hands: &mut [PlayerHand]) {
+        let mut previous_spot = None;
+        for hand in finished_hands {
+            // The first hand of each spot holds the original bet, the others were split from it
+            let original = previous_spot != Some(hand.spot);
+            previous_spot = Some(hand.spot);
+            if hand.status == Status::Surrendered {
+                hand.winnings = 0;
+            }
+            if self.rules.original_bets_only && hand.status != Status::Blackjack {
+                hand.winnings = if original { hand.bet - hand.initial_bet() } else { hand.bet };
+            }
+        }
+    }
+
     /// The dealer pays out the player's winnings.
     /// If the player has no chips left, the game is over.
     /// Otherwise, the dealer will shuffle the shoe if necessary, or the game will return to betting.
     fn pay_out_winnings(&mut self, total_winnings: u32) -> GameState {
         self.chips += total_winnings;
-        if self
-            .rules
-            .min_bet
-            .map_or(self.chips == 0, |min| self.chips < min)
-        {
+        if self.is_out_of_chips() {
             GameState::GameOver
-        } else if self.shoe.needs_shuffle() {
-            if self.fast_forward {
-                self.shuffle_dispenser()
-            } else {
-                GameState::Shuffle
-            }
         } else {
+            self.start_next_round()
+        }
+    }
+
+    /// The dealer shuffles if the shoe needs it, and the next round starts.
+    fn start_next_round(&mut self) -> GameState {
+        if !self.shoe.needs_shuffle() {
             GameState::Betting
+        } else if self.fast_forward {
+            self.shuffle_dispenser()
+        } else {
+            GameState::Shuffle
         }
     }
 
+    /// Returns whether the player cannot afford the minimum bet anymore.
+    pub(crate) fn is_out_of_chips(&self) -> bool {
+        self.rules
+            .min_bet
+            .map_or(self.chips == 0, |min| self.chips < min)
+    }
+
     /// The dealer shuffles the shoe.
     /// The game returns to the betting state.
     fn shuffle_dispenser(&mut self) -> GameState {
@@ -841,6 +1317,12 @@ impl Table {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::basic_strategy::BasicStrategy;
+    use crate::card::hand::{Bonus, Suits};
+    use crate::card::{Card, Rank, Suit};
+    use crate::rules::{BlackjackPayout, PairDefinition, SuitedBlackjackBonus};
+    use crate::strategy;
+    use crate::test_helpers::{card, dealer, hand_of, suited};
 
     #[test]
     fn test_bet() {
@@ -854,21 +1336,475 @@ mod tests {
             },
         );
         assert_eq!(
-            table.bet(50),
-            Ok(GameState::DealFirstPlayerCard { bet: 50 })
+            table.bet(vec![50.into()]),
+            Ok(GameState::DealFirstPlayerCard { wagers: vec![50.into()] })
         );
         assert_eq!(
-            table.bet(101),
+            table.bet(vec![101.into()]),
             Err((GameState::Betting, Error::BetError(BetError::TooHigh)))
         );
         assert_eq!(
-            table.bet(0),
+            table.bet(vec![0.into()]),
             Err((GameState::Betting, Error::BetError(BetError::TooLow)))
         );
-        assert_eq!(table.bet(1), Ok(GameState::DealFirstPlayerCard { bet: 1 }));
+        assert_eq!(table.bet(vec![1.into()]), Ok(GameState::DealFirstPlayerCard { wagers: vec![1.into()] }));
+        assert_eq!(
+            table.bet(vec![50.into()]),
+            Err((GameState::Betting, Error::BetError(BetError::CantAfford)))
+        );
+        // Bets are checked just the same when fast-forwarding
+        table.fast_forward = true;
         assert_eq!(
-            table.bet(50),
+            table.bet(vec![50.into()]),
             Err((GameState::Betting, Error::BetError(BetError::CantAfford)))
         );
+        assert_eq!(
+            table.bet(vec![Wager { bet: 10, side_bets: vec![5] }]),
+            Err((GameState::Betting, Error::BetError(BetError::SideBetNotOffered)))
+        );
+    }
+
+    #[test]
+    fn test_sit_out() {
+        let mut table = Table::new(100, Shoe::new(4, 0.50), Rules::default());
+        // The player watches the dealer deal the round without them, and sees every card
+        assert_eq!(table.progress(GameState::Betting, Some(Input::SitOut)), Ok(GameState::Betting));
+        let seen = (2..=11).map(|value| table.shoe.cards_seen(value)).sum::<u16>();
+        assert!(table.shoe.cards_drawn >= 2);
+        assert_eq!(seen, table.shoe.cards_drawn);
+        assert_eq!(table.shoe.shuffles, 0);
+        assert_eq!(table.chips, 100);
+        assert_eq!(table.statistics.rounds_sat_out(), 1);
+        assert_eq!(table.statistics.turns_played(), 0);
+        // The strategy sits out by betting nothing
+        let mut strategy = crate::betting::WithBetting::new(BasicStrategy, crate::betting::FlatBet(0));
+        assert!(matches!(strategy::input(&mut strategy, &table, &GameState::Betting), Some(Input::SitOut)));
+    }
+
+    #[test]
+    fn test_insured_dealer_blackjack() {
+        let mut hand = hand_of([Rank::King, Rank::Nine].map(card), 100);
+        hand.stand();
+        let dealer_hand = dealer([Rank::Ace, Rank::Queen].map(card));
+        // The player has already put up their bet of 100 and insurance of 50
+        let mut table = Table::new(1_000, Shoe::new(6, 0.75), Rules::default());
+        table.fast_forward = true;
+        table.end_round(vec![hand], dealer_hand, 50);
+        // Insurance pays 2:1, which makes up for the lost bet, and the insurance bet is returned
+        assert_eq!(table.chips, 1_000 + 150);
+    }
+
+    #[test]
+    fn test_hole_card_is_seen_once_revealed() {
+        let mut table = Table::new(1_000_000, Shoe::with_seed(6, 0.75, 8), Rules::default());
+        table.fast_forward = true;
+        let mut strategy = BasicStrategy;
+        let seen = |shoe: &Shoe| (2..=11).map(|value| shoe.cards_seen(value)).sum::<u16>();
+        let mut state = GameState::Betting;
+        for _ in 0..1_000 {
+            let input = strategy::input(&mut strategy, &table, &state);
+            state = table.progress(state, input).unwrap();
+            match state {
+                GameState::PlayPlayerTurn { .. } => {
+                    assert_eq!(seen(&table.shoe), table.shoe.cards_drawn - 1);
+                }
+                GameState::Betting => assert_eq!(seen(&table.shoe), table.shoe.cards_drawn),
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_aces_receive_one_card() {
+        let mut table = Table::new(
+            1_000,
+            Shoe::with_seed(6, 0.75, 3),
+            Rules {
+                resplit_aces: false,
+                split_aces_one_card: true,
+                ..Rules::default()
+            },
+        );
+        let mut state = GameState::PlayPlayerTurn {
+            player_turn: hand_of([Rank::Ace, Rank::Ace].map(card), 100).into(),
+            dealer_hand: dealer([card(Rank::Six)]),
+            insurance_bet: 0,
+        };
+        let mut input = Some(Input::Action(HandAction::Split));
+        // Both split aces stand after their one card, so the player's turn ends by itself
+        while !matches!(state, GameState::RevealHoleCard { .. }) {
+            state = table.progress(state, input.take()).unwrap();
+        }
+        let GameState::RevealHoleCard { finished_hands, .. } = state else { unreachable!() };
+        assert_eq!(finished_hands.len(), 2);
+        assert!(finished_hands.iter().all(|hand| hand.size() == 2));
+
+        // Without the one card rule, split aces may still be forbidden from hitting
+        table.rules.split_aces_one_card = false;
+        table.rules.hit_split_aces = false;
+        let mut player_turn = PlayerTurn::from(hand_of([Rank::Ace, Rank::Ace].map(card), 100));
+        let new_hand = player_turn.current_hand_mut().split();
+        player_turn.defer(new_hand);
+        *player_turn.current_hand_mut() += card(Rank::Six);
+        assert_eq!(
+            table.check_hit_allowed(&player_turn),
+            Err(SplitError::HitSplitAcesNotAllowed)
+        );
+        assert_eq!(
+            table.check_double_allowed(&player_turn),
+            Err(DoubleError::HitSplitAcesNotAllowed)
+        );
+        table.rules.hit_split_aces = true;
+        assert_eq!(table.check_double_allowed(&player_turn), Ok(()));
+    }
+
+    #[test]
+    fn test_double_restriction() {
+        let turn = |ranks: &[Rank]| PlayerTurn::from(hand_of(ranks.iter().cloned().map(card), 100));
+        let mut table = Table::new(1_000, Shoe::new(6, 0.75), Rules::default());
+        table.rules.double_restriction = DoubleRestriction::TenToEleven;
+        assert_eq!(table.check_double_allowed(&turn(&[Rank::Six, Rank::Four])), Ok(()));
+        assert_eq!(
+            table.check_double_allowed(&turn(&[Rank::Six, Rank::Three])),
+            Err(DoubleError::TotalNotAllowed)
+        );
+        assert_eq!(
+            table.check_double_allowed(&turn(&[Rank::Ace, Rank::Seven])),
+            Err(DoubleError::TotalNotAllowed)
+        );
+        assert_eq!(
+            table.check_double_allowed(&turn(&[Rank::Two, Rank::Three, Rank::Five])),
+            Err(DoubleError::NotTwoCards)
+        );
+        table.rules.double_restriction = DoubleRestriction::AnyCards;
+        assert_eq!(table.check_double_allowed(&turn(&[Rank::Two, Rank::Three, Rank::Five])), Ok(()));
+    }
+
+    #[test]
+    fn test_split_pairs_by_value() {
+        let mut player_turn = PlayerTurn::from(hand_of([Rank::Jack, Rank::Queen].map(card), 100));
+        let mut table = Table::new(1_000, Shoe::new(6, 0.75), Rules::default());
+        assert_eq!(table.check_split_allowed(&player_turn), Err(SplitError::NotAPair));
+        table.rules.pair_definition = PairDefinition::SameValue;
+        assert_eq!(table.check_split_allowed(&player_turn), Ok(()));
+        // Jacks may be split once, but not resplit, while other tens may still be resplit
+        table.rules.max_splits_by_rank[Rank::Jack as usize] = Some(1);
+        let mut new_hand = player_turn.current_hand_mut().split();
+        *player_turn.current_hand_mut() += card(Rank::King);
+        new_hand += card(Rank::Ten);
+        player_turn.defer(new_hand);
+        assert_eq!(table.check_split_allowed(&player_turn), Err(SplitError::MaxSplitsReached));
+        player_turn.next_hand();
+        assert_eq!(table.check_split_allowed(&player_turn), Ok(()));
+        assert_eq!(table.rules.max_splits_of_value(10), table.rules.max_splits);
+    }
+
+    #[test]
+    fn test_fractional_payouts() {
+        let hand = hand_of([Rank::Ace, Rank::King].map(card), 5);
+        let dealer_hand = dealer([Rank::Ten, Rank::Queen].map(card));
+        let paying = |blackjack_payout| Rules { blackjack_payout, ..Rules::default() };
+        assert_eq!(hand.calculate_winnings(&dealer_hand, &paying(BlackjackPayout::ThreeToTwo)), (12, 0.5));
+        assert_eq!(hand.calculate_winnings(&dealer_hand, &paying(BlackjackPayout::SixToFive)), (11, 0.0));
+        assert_eq!(hand.calculate_winnings(&dealer_hand, &paying(BlackjackPayout::Custom(4, 3))), (11, 2.0 / 3.0));
+
+        let mut table = Table::new(1_000, Shoe::new(6, 0.75), Rules::default());
+        assert_eq!(table.collect_fraction(0.5), 0);
+        table.rules.chip_rounding = ChipRounding::HalfChips;
+        assert_eq!(table.collect_fraction(2.0 / 3.0), 0);
+        assert_eq!(table.collect_fraction(0.5), 1);
+        table.rules.chip_rounding = ChipRounding::Exact;
+        assert_eq!((0..5).map(|_| table.collect_fraction(0.2)).sum::<u32>(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Payout denominator must not be zero")]
+    fn test_zero_payout_denominator_is_not_dealt() {
+        let rules = Rules {
+            blackjack_payout: BlackjackPayout::Custom(3, 0),
+            ..Rules::default()
+        };
+        let _ = Table::new(1_000, Shoe::new(6, 0.75), rules);
+    }
+
+    #[test]
+    fn test_spanish_21() {
+        let rules = Rules::spanish_21();
+        let dealer_hand = dealer([Rank::King, Rank::Six, Rank::Five].map(card));
+        // A player's 21 beats the dealer's 21, and special 21s pay bonuses
+        let mixed_21 = hand_of(
+            [suited(Rank::Nine, Suit::Hearts), suited(Rank::Two, Suit::Clubs), suited(Rank::King, Suit::Clubs)],
+            10,
+        );
+        assert_eq!(mixed_21.calculate_winnings(&dealer_hand, &rules), (20, 0.0));
+        assert_eq!(mixed_21.calculate_winnings(&dealer_hand, &Rules::default()), (10, 0.0));
+        let spades = [Rank::Seven, Rank::Seven, Rank::Seven].map(|rank| suited(rank, Suit::Spades));
+        assert_eq!(hand_of(spades, 10).calculate_winnings(&dealer_hand, &rules), (40, 0.0));
+        let five_cards = [Rank::Two, Rank::Three, Rank::Four, Rank::Five, Rank::Seven].map(card);
+        assert_eq!(hand_of(five_cards, 10).calculate_winnings(&dealer_hand, &rules), (25, 0.0));
+
+        // A doubled hand can be rescued by surrendering it, but not hit again
+        let mut table = Table::new(1_000, Shoe::new(6, 0.75).without_rank(&Rank::Ten), rules);
+        let mut doubled = hand_of([Rank::Six, Rank::Five].map(card), 10);
+        doubled.double_with_rescue(card(Rank::Two));
+        let player_turn = PlayerTurn::from(vec![doubled]);
+        assert_eq!(table.check_hit_allowed(&player_turn), Err(SplitError::HitDoubledHandNotAllowed));
+        assert_eq!(table.check_double_allowed(&player_turn), Err(DoubleError::AlreadyDoubled));
+        assert_eq!(table.check_surrender_allowed(player_turn.current_hand()), Ok(()));
+        let mut rescued = player_turn.into_hands().remove(0);
+        rescued.surrender();
+        assert_eq!(rescued.calculate_winnings(&dealer_hand, &table.rules), (10, 0.0));
+
+        // The shoe has no tens, but still has the other ten-valued cards
+        let cards: Vec<Card> = (0..table.shoe.total_cards()).map(|_| table.shoe.draw_card()).collect();
+        assert_eq!(cards.len(), 6 * 48);
+        assert!(cards.iter().all(|card| card.rank != Rank::Ten));
+        assert_eq!(cards.iter().filter(|card| card.rank == Rank::King).count(), 6 * 4);
+    }
+
+    #[test]
+    fn test_blackjack_switch() {
+        let mut table = Table::new(1_000, Shoe::new(6, 0.75), Rules::blackjack_switch());
+        let switch_error = Err((GameState::Betting, Error::BetError(BetError::SwitchNeedsTwoHands)));
+        assert_eq!(table.bet(vec![100.into()]), switch_error);
+        assert_eq!(table.bet(vec![100.into(), 200.into()]), switch_error);
+
+        // Switching the second cards turns a soft 16 and a 15 into a blackjack and a 10
+        let player_hands = vec![
+            hand_of([Rank::Ace, Rank::Five].map(card), 100),
+            hand_of([Rank::Five, Rank::King].map(card), 100),
+        ];
+        let mut dealer_hand = dealer([Rank::Nine, Rank::Six].map(card));
+        let state = table.start_player_turn(player_hands, dealer_hand.clone());
+        assert!(matches!(state, GameState::OfferSwitch { .. }));
+        let state = table.progress(state, Some(Input::Choice(true))).unwrap();
+        let GameState::PlayPlayerTurn { player_turn, .. } = state else {
+            panic!("expected the player's turn")
+        };
+        let hands = player_turn.into_hands();
+        assert_eq!(hands[0].status, Status::Blackjack);
+        assert_eq!((hands[1].status.clone(), hands[1].value.total), (Status::InPlay, 10));
+
+        // Blackjack only pays 1:1, but still beats a dealer 22, which pushes every other hand
+        dealer_hand += card(Rank::Seven);
+        assert_eq!(dealer_hand.value.total, 22);
+        assert_eq!(hands[0].calculate_winnings(&dealer_hand, &table.rules), (200, 0.0));
+        let mut stood = hand_of([Rank::Ten, Rank::Eight].map(card), 100);
+        stood.stand();
+        assert_eq!(stood.calculate_winnings(&dealer_hand, &table.rules), (100, 0.0));
+        assert_eq!(stood.calculate_winnings(&dealer_hand, &Rules::default()), (200, 0.0));
+    }
+
+    #[test]
+    fn test_free_bet() {
+        let mut table = Table::new(1_000, Shoe::with_seed(6, 0.75, 3), Rules::free_bet());
+        let pair = hand_of([Rank::Eight, Rank::Eight].map(card), 100);
+        let dealer_hand = dealer([Rank::Six, Rank::Ten].map(card));
+
+        // Splitting eights is free, so the house puts up the bet on the new hand
+        let state = GameState::PlayPlayerTurn {
+            player_turn: pair.into(),
+            dealer_hand,
+            insurance_bet: 0,
+        };
+        let mut state = table.progress(state, Some(Input::Action(HandAction::Split))).unwrap();
+        while !matches!(state, GameState::PlayPlayerTurn { .. }) {
+            state = table.progress(state, None).unwrap();
+        }
+        let GameState::PlayPlayerTurn { player_turn, .. } = state else { unreachable!() };
+        assert_eq!(table.chips, 1_000);
+        let hands = player_turn.into_hands();
+        assert_eq!((hands[0].free_bet, hands[1].free_bet), (0, 100));
+        assert!(!table.is_free_split(&hand_of([Rank::Ten, Rank::Ten].map(card), 100)));
+
+        // A free double only risks the original bet, but is paid on both
+        let mut doubled = hand_of([Rank::Six, Rank::Five].map(card), 100);
+        assert!(table.is_free_double(&doubled));
+        doubled.free_bet = doubled.bet;
+        doubled.double(card(Rank::Nine));
+        let mut settle = |dealer_hand: DealerHand| {
+            doubled.winnings = doubled.calculate_winnings(&dealer_hand, &table.rules).0;
+            (doubled.paid_bet(), doubled.payout())
+        };
+        assert_eq!(settle(dealer([Rank::King, Rank::Nine].map(card))), (100, 300));
+        assert_eq!(settle(dealer([Rank::Six, Rank::Six, Rank::King].map(card))), (100, 100));
+        assert_eq!(settle(dealer([Rank::King, Rank::Ace].map(card))), (100, 0));
+    }
+
+    #[test]
+    fn test_charlie_and_suited_blackjack() {
+        let rules = Rules {
+            charlie: Some(5),
+            suited_blackjack_bonus: Some(SuitedBlackjackBonus { suited: (2, 1), spades: (3, 1) }),
+            ..Rules::default()
+        };
+        let mut table = Table::new(1_000, Shoe::with_seed(6, 0.75, 5), rules);
+        let dealer_20 = dealer([Rank::King, Rank::Queen].map(|rank| suited(rank, Suit::Clubs)));
+        let dealer_blackjack = dealer([Rank::King, Rank::Ace].map(|rank| suited(rank, Suit::Clubs)));
+
+        // Hitting a four-card 9 always makes a five-card Charlie, which beats anything but a blackjack
+        let state = GameState::PlayPlayerTurn {
+            player_turn: hand_of([Rank::Two, Rank::Two, Rank::Two, Rank::Three].map(card), 100).into(),
+            dealer_hand: dealer_20.clone(),
+            insurance_bet: 0,
+        };
+        let mut state = table.progress(state, Some(Input::Action(HandAction::Hit))).unwrap();
+        while !matches!(state, GameState::RevealHoleCard { .. }) {
+            state = table.progress(state, None).unwrap();
+        }
+        let GameState::RevealHoleCard { finished_hands, .. } = state else { unreachable!() };
+        let charlie = &finished_hands[0];
+        assert_eq!(charlie.status, Status::Charlie);
+        assert_eq!(charlie.calculate_winnings(&dealer_20, &table.rules), (200, 0.0));
+        assert_eq!(charlie.calculate_winnings(&dealer_blackjack, &table.rules), (0, 0.0));
+
+        // Suited blackjacks pay a bonus instead of 3:2
+        let blackjack = |[first, second]: [Suit; 2]| {
+            hand_of([suited(Rank::Ace, first), suited(Rank::Jack, second)], 100)
+        };
+        let rules = &table.rules;
+        assert_eq!(blackjack([Suit::Hearts, Suit::Clubs]).calculate_winnings(&dealer_20, rules), (250, 0.0));
+        assert_eq!(blackjack([Suit::Hearts, Suit::Hearts]).calculate_winnings(&dealer_20, rules), (300, 0.0));
+        let spades = blackjack([Suit::Spades, Suit::Spades]);
+        assert_eq!(spades.calculate_winnings(&dealer_20, rules), (400, 0.0));
+        assert_eq!(spades.bonus(rules), Some((Bonus::SuitedBlackjack(Suits::Spades), (3, 1))));
+        assert_eq!(spades.calculate_winnings(&dealer_blackjack, rules), (100, 0.0));
+    }
+
+    #[test]
+    fn test_even_money() {
+        let mut table = Table::new(1_000, Shoe::new(6, 0.75), Rules::default());
+        let player_hand = hand_of([Rank::Ace, Rank::Jack].map(card), 100);
+        let dealer_hand = dealer([Rank::Ace, Rank::King].map(card));
+        // Even money is paid even though the dealer has blackjack as well
+        let mut state = table
+            .progress(
+                GameState::OfferEvenMoney {
+                    player_turn: player_hand.into(),
+                    dealer_hand,
+                    insurance_bet: 0,
+                },
+                Some(Input::Choice(true)),
+            )
+            .unwrap();
+        while !matches!(state, GameState::Payout { .. }) {
+            state = table.progress(state, None).unwrap();
+        }
+        assert_eq!(state, GameState::Payout { total_bet: 100, total_winnings: 200 });
+    }
+
+    #[test]
+    fn test_multiple_spots() {
+        let mut table = Table::new(1_000, Shoe::with_seed(6, 0.75, 5), Rules::default());
+        assert_eq!(
+            table.bet(vec![100.into(); 4]),
+            Err((GameState::Betting, Error::BetError(BetError::TooManySpots)))
+        );
+        // The spots are dealt one card each in turn, before and after the dealer's up card
+        let mut shoe = table.shoe.clone();
+        let cards: Vec<Card> = (0..5).map(|_| shoe.draw_card()).collect();
+        let mut state = table.bet(vec![100.into(), 0.into(), 200.into()]).unwrap();
+        assert_eq!(table.chips, 700);
+        while !matches!(state, GameState::DealHoleCard { .. }) {
+            state = table.progress(state, None).unwrap();
+        }
+        let GameState::DealHoleCard { player_hands, dealer_hand } = state else { unreachable!() };
+        assert_eq!(player_hands[0].cards, [cards[0].clone(), cards[3].clone()]);
+        assert_eq!(player_hands[1].cards, [cards[1].clone(), cards[4].clone()]);
+        // The empty spot keeps its position, so the second hand is played on the third spot
+        assert_eq!((player_hands[1].spot, player_hands[1].bet), (2, 200));
+        assert_eq!(
+            table.bet(vec![0.into(), 0.into(), 0.into(), 100.into()]),
+            Err((GameState::Betting, Error::BetError(BetError::TooManySpots)))
+        );
+        assert_eq!(dealer_hand.showing(), cards[2].rank.worth());
+
+        // Split hands are played right after the hand they were split from, before the next spot
+        let pair = hand_of([Rank::Eight, Rank::Eight].map(card), 100);
+        let mut next_spot = hand_of([card(Rank::Ten)], 100);
+        next_spot.spot = 1;
+        let mut player_turn = PlayerTurn::from(vec![pair, next_spot]);
+        let new_hand = player_turn.current_hand_mut().split();
+        player_turn.defer(new_hand);
+        assert_eq!(player_turn.hands_in_spot(), 2);
+        let spots: Vec<u8> = player_turn.into_hands().iter().map(|hand| hand.spot).collect();
+        assert_eq!(spots, [0, 0, 1]);
+    }
+
+    #[test]
+    fn test_stacked_shoe() {
+        // The player is dealt 10 and 9, and the dealer a blackjack
+        let shoe = Shoe::stacked([Rank::Ten, Rank::Ace, Rank::Nine, Rank::King].map(card).into());
+        let mut table = Table::new(1_000, shoe, Rules::default());
+        let mut state = table.bet(vec![100.into()]).unwrap();
+        while !matches!(state, GameState::Payout { .. }) {
+            state = table.progress(state, None).unwrap();
+        }
+        assert_eq!(state, GameState::Payout { total_bet: 100, total_winnings: 0 });
+
+        // The player splits eights and is dealt another eight, which cannot be split again
+        let ranks = [Rank::Eight, Rank::Six, Rank::Eight, Rank::Ten, Rank::Eight, Rank::Three];
+        let shoe = Shoe::stacked(ranks.map(card).into());
+        let rules = Rules { max_splits: Some(1), ..Rules::default() };
+        let mut table = Table::new(1_000, shoe, rules);
+        let mut state = table.bet(vec![100.into()]).unwrap();
+        while !matches!(state, GameState::PlayPlayerTurn { .. }) {
+            state = table.progress(state, None).unwrap();
+        }
+        state = table.progress(state, Some(Input::Action(HandAction::Split))).unwrap();
+        while !matches!(state, GameState::PlayPlayerTurn { .. }) {
+            state = table.progress(state, None).unwrap();
+        }
+        let GameState::PlayPlayerTurn { player_turn, .. } = state else { unreachable!() };
+        assert_eq!(player_turn.hands(), 2);
+        assert!(player_turn.current_hand().is_pair());
+        assert_eq!(table.check_split_allowed(&player_turn), Err(SplitError::MaxSplitsReached));
+    }
+
+    #[test]
+    fn test_late_dealer_blackjack_takes_doubles_and_splits() {
+        let play = |rules: Rules| {
+            // The player splits eights against a ten and doubles the first hand, then the dealer
+            // draws an ace for blackjack
+            let ranks = [Rank::Eight, Rank::Ten, Rank::Eight, Rank::Three, Rank::Nine, Rank::Nine, Rank::Ace];
+            let shoe = Shoe::stacked(ranks.map(card).into());
+            let mut table = Table::new(1_000, shoe, rules);
+            let mut actions = [HandAction::Split, HandAction::Double, HandAction::Stand].into_iter();
+            let mut state = table.bet(vec![100.into()]).unwrap();
+            while !matches!(state, GameState::Payout { .. }) {
+                let input = match state {
+                    GameState::PlayPlayerTurn { .. } => actions.next().map(Input::Action),
+                    _ => None,
+                };
+                state = table.progress(state, input).unwrap();
+            }
+            let GameState::Payout { total_bet, total_winnings } = state else { unreachable!() };
+            table.progress(state, None).unwrap();
+            (total_bet, total_winnings, table.chips)
+        };
+        let enhc = Rules { no_hole_card: true, ..Rules::default() };
+        assert_eq!(play(enhc.clone()), (300, 0, 700));
+        // Only the original bet is lost, and the double and the split are returned
+        let obo = Rules { original_bets_only: true, ..enhc };
+        assert_eq!(play(obo), (300, 200, 900));
+    }
+
+    #[test]
+    fn test_late_dealer_blackjack_beats_charlie() {
+        // The player hits a pair of twos into a five-card Charlie against a ten,
+        // then the dealer draws an ace for blackjack
+        let ranks = [Rank::Two, Rank::Ten, Rank::Two, Rank::Two, Rank::Three, Rank::Two, Rank::Ace];
+        let shoe = Shoe::stacked(ranks.map(card).into());
+        let rules = Rules { charlie: Some(5), no_hole_card: true, ..Rules::default() };
+        let mut table = Table::new(1_000, shoe, rules);
+        let mut state = table.bet(vec![100.into()]).unwrap();
+        while !matches!(state, GameState::Payout { .. }) {
+            let input = match state {
+                GameState::PlayPlayerTurn { .. } => Some(Input::Action(HandAction::Hit)),
+                _ => None,
+            };
+            state = table.progress(state, input).unwrap();
+        }
+        assert_eq!(state, GameState::Payout { total_bet: 100, total_winnings: 0 });
     }
 }
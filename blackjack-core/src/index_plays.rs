@@ -0,0 +1,495 @@
+//! Count-dependent deviations from basic strategy, also known as index plays.
+//! Each index play says which action to take in a situation once the true count reaches
+//! (or drops below) a threshold. When no index play applies, basic strategy is followed.
+//!
+//! Index plays are written one per line in a simple text format, e.g. `hard 16 vs 10 stand >= 0`.
+//! See `data/illustrious-18-fab-4.txt` for a description of the format.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::basic_strategy::{self, BasicStrategy, PreferredAction};
+use crate::card::hand::{PlayerHand, PlayerTurn};
+use crate::counting::CountSystem;
+use crate::game::HandAction;
+use crate::strategy::{DealerView, Strategy, TableView};
+
+/// A kind of player hand, as it appears in strategy charts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandKind {
+    /// A hard total which is not a splittable pair
+    Hard(u8),
+    /// A soft total which is not a splittable pair
+    Soft(u8),
+    /// A splittable pair of cards with the given value
+    Pair(u8),
+}
+
+impl HandKind {
+    /// Returns whether the current hand of the player's turn is of this kind.
+    fn matches(self, table: TableView, player_turn: &PlayerTurn) -> bool {
+        let hand = player_turn.current_hand();
+        let splittable = table.check_split_allowed(player_turn).is_ok();
+        self.matches_hand(hand, splittable)
+    }
+
+    fn matches_hand(self, hand: &PlayerHand, splittable: bool) -> bool {
+        match self {
+            Self::Hard(total) => !splittable && !hand.value.soft && hand.value.total == total,
+            Self::Soft(total) => !splittable && hand.value.soft && hand.value.total == total,
+            Self::Pair(card) => splittable && hand.cards[0].rank.worth() == card,
+        }
+    }
+}
+
+/// The decision an index play applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Situation {
+    /// Whether to take insurance
+    Insurance,
+    /// Whether to surrender the given hand against the given up card before the dealer peeks
+    EarlySurrender(HandKind, u8),
+    /// How to play the given hand against the given up card
+    Play(HandKind, u8),
+}
+
+/// The action to take when an index play applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexAction {
+    /// Take the offered insurance or early surrender
+    Take,
+    /// Decline the offered insurance or early surrender
+    Decline,
+    /// Play the hand, falling back to the alternative if the preferred action is not allowed
+    Play(PreferredAction),
+}
+
+/// Whether an index play applies at or above its threshold, or below it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Threshold {
+    AtLeast(f32),
+    Below(f32),
+}
+
+impl Threshold {
+    /// Returns whether the given true count satisfies this threshold.
+    #[must_use]
+    pub fn contains(self, true_count: f32) -> bool {
+        match self {
+            Self::AtLeast(threshold) => true_count >= threshold,
+            Self::Below(threshold) => true_count < threshold,
+        }
+    }
+}
+
+/// A single deviation from basic strategy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IndexPlay {
+    pub situation: Situation,
+    pub action: IndexAction,
+    pub threshold: Threshold,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidSituation(usize),
+    InvalidAction(usize),
+    InvalidThreshold(usize),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSituation(line) => write!(f, "Invalid situation on line {line}"),
+            Self::InvalidAction(line) => write!(f, "Invalid action on line {line}"),
+            Self::InvalidThreshold(line) => write!(f, "Invalid threshold on line {line}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A set of index plays. When several index plays apply to a situation, the first one is used.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndexPlays {
+    pub plays: Vec<IndexPlay>,
+}
+
+impl IndexPlays {
+    /// The Illustrious 18 and the Fab 4 for Hi-Lo.
+    #[must_use]
+    pub fn illustrious_18_and_fab_4() -> Self {
+        include_str!("../data/illustrious-18-fab-4.txt")
+            .parse()
+            .expect("bundled index plays are valid")
+    }
+
+    /// Returns the action of the first index play for the given situation which applies at the
+    /// given true count, if any.
+    fn find(&self, true_count: f32, situation: impl Fn(&Situation) -> bool) -> Option<IndexAction> {
+        self.plays
+            .iter()
+            .find(|play| situation(&play.situation) && play.threshold.contains(true_count))
+            .map(|play| play.action)
+    }
+
+    /// Returns whether to take insurance at the given true count, if an index play applies.
+    #[must_use]
+    pub fn insurance(&self, true_count: f32) -> Option<bool> {
+        self.find(true_count, |situation| *situation == Situation::Insurance)
+            .map(|action| action == IndexAction::Take)
+    }
+
+    /// Returns whether to surrender early at the given true count, if an index play applies.
+    #[must_use]
+    pub fn surrender_early(
+        &self,
+        true_count: f32,
+        table: TableView,
+        player_hand: &PlayerHand,
+        dealer_hand: DealerView,
+    ) -> Option<bool> {
+        let splittable = player_hand.is_pair_by(table.rules().pair_definition);
+        self.find(true_count, |situation| match situation {
+            Situation::EarlySurrender(kind, up_card) => {
+                *up_card == dealer_hand.showing() && kind.matches_hand(player_hand, splittable)
+            }
+            _ => false,
+        })
+        .map(|action| action == IndexAction::Take)
+    }
+
+    /// Returns the preferred action for the current hand at the given true count,
+    /// if an index play applies.
+    #[must_use]
+    pub fn play_hand(
+        &self,
+        true_count: f32,
+        table: TableView,
+        player_turn: &PlayerTurn,
+        dealer_hand: DealerView,
+    ) -> Option<PreferredAction> {
+        self.find(true_count, |situation| match situation {
+            Situation::Play(kind, up_card) => {
+                *up_card == dealer_hand.showing() && kind.matches(table, player_turn)
+            }
+            _ => false,
+        })
+        .and_then(|action| match action {
+            IndexAction::Play(preferred) => Some(preferred),
+            IndexAction::Take | IndexAction::Decline => None,
+        })
+    }
+}
+
+impl FromStr for IndexPlays {
+    type Err = ParseError;
+
+    /// Parses index plays from text with one index play per line.
+    /// Empty lines and everything after a `#` are ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut plays = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            let line_number = i + 1;
+            let words: Vec<&str> = line.split('#').next().unwrap_or_default().split_whitespace().collect();
+            if words.is_empty() {
+                continue;
+            }
+            // The action, comparison and threshold are always the last three words
+            let Some(split) = words.len().checked_sub(3) else {
+                return Err(ParseError::InvalidSituation(line_number));
+            };
+            let situation = parse_situation(&words[..split])
+                .ok_or(ParseError::InvalidSituation(line_number))?;
+            let action = parse_action(situation, words[split])
+                .ok_or(ParseError::InvalidAction(line_number))?;
+            let threshold = parse_threshold(words[split + 1], words[split + 2])
+                .ok_or(ParseError::InvalidThreshold(line_number))?;
+            plays.push(IndexPlay { situation, action, threshold });
+        }
+        Ok(Self { plays })
+    }
+}
+
+fn parse_situation(words: &[&str]) -> Option<Situation> {
+    match words {
+        ["insurance"] => Some(Situation::Insurance),
+        ["early", hand @ ..] => {
+            let (kind, up_card) = parse_hand(hand)?;
+            Some(Situation::EarlySurrender(kind, up_card))
+        }
+        hand => {
+            let (kind, up_card) = parse_hand(hand)?;
+            Some(Situation::Play(kind, up_card))
+        }
+    }
+}
+
+fn parse_hand(words: &[&str]) -> Option<(HandKind, u8)> {
+    let [kind, value, "vs", up_card] = words else {
+        return None;
+    };
+    let value: u8 = value.parse().ok()?;
+    let up_card: u8 = up_card.parse().ok().filter(|card| (2..=11).contains(card))?;
+    let kind = match *kind {
+        "hard" if (4..=21).contains(&value) => HandKind::Hard(value),
+        "soft" if (12..=21).contains(&value) => HandKind::Soft(value),
+        "pair" if (2..=11).contains(&value) => HandKind::Pair(value),
+        _ => return None,
+    };
+    Some((kind, up_card))
+}
+
+fn parse_action(situation: Situation, word: &str) -> Option<IndexAction> {
+    match (situation, word) {
+        (Situation::Insurance, "take") | (Situation::EarlySurrender(..), "surrender") => {
+            Some(IndexAction::Take)
+        }
+        (Situation::Insurance | Situation::EarlySurrender(..), "decline") => Some(IndexAction::Decline),
+        (Situation::Play(..), word) => {
+            let preferred = match word {
+                "stand" => PreferredAction::Stand,
+                "hit" => PreferredAction::Hit,
+                "split" => PreferredAction::Split,
+                "double-or-hit" => PreferredAction::DoubleOrHit,
+                "double-or-stand" => PreferredAction::DoubleOrStand,
+                "surrender-or-hit" => PreferredAction::SurrenderOrHit,
+                "surrender-or-stand" => PreferredAction::SurrenderOrStand,
+                "surrender-or-split" => PreferredAction::SurrenderOrSplit,
+                _ => return None,
+            };
+            Some(IndexAction::Play(preferred))
+        }
+        _ => None,
+    }
+}
+
+fn parse_threshold(comparison: &str, true_count: &str) -> Option<Threshold> {
+    let true_count = true_count.parse().ok()?;
+    match comparison {
+        ">=" => Some(Threshold::AtLeast(true_count)),
+        "<" => Some(Threshold::Below(true_count)),
+        _ => None,
+    }
+}
+
+/// A player who follows basic strategy, but deviates from it according to a set of index plays.
+/// Insurance is taken for half the original bet.
+#[derive(Debug, Clone)]
+pub struct IndexStrategy {
+    /// The count system used to calculate the true count
+    pub system: CountSystem,
+    /// The deviations from basic strategy
+    pub index_plays: IndexPlays,
+}
+
+impl IndexStrategy {
+    #[must_use]
+    pub const fn new(system: CountSystem, index_plays: IndexPlays) -> Self {
+        Self { system, index_plays }
+    }
+}
+
+impl Default for IndexStrategy {
+    /// The Illustrious 18 and the Fab 4 with Hi-Lo.
+    fn default() -> Self {
+        Self::new(CountSystem::HI_LO, IndexPlays::illustrious_18_and_fab_4())
+    }
+}
+
+impl Strategy for IndexStrategy {
+    fn bet(&mut self, _table: TableView) -> u32 {
+        basic_strategy::bet()
+    }
+
+    fn bet_spots(&mut self, table: TableView) -> Vec<u32> {
+        BasicStrategy.bet_spots(table)
+    }
+
+    fn side_bets(&mut self, table: TableView) -> Vec<u32> {
+        BasicStrategy.side_bets(table)
+    }
+
+    fn surrender_early(
+        &mut self,
+        table: TableView,
+        player_hand: &PlayerHand,
+        dealer_hand: DealerView,
+    ) -> bool {
+        let true_count = self.system.true_count(table.shoe());
+        self.index_plays
+            .surrender_early(true_count, table, player_hand, dealer_hand)
+            .unwrap_or_else(|| basic_strategy::surrender_early(table, player_hand, dealer_hand))
+    }
+
+    fn bet_insurance(
+        &mut self,
+        table: TableView,
+        player_hand: &PlayerHand,
+        _dealer_hand: DealerView,
+    ) -> u32 {
+        let true_count = self.system.true_count(table.shoe());
+        match self.index_plays.insurance(true_count) {
+            Some(true) => (player_hand.bet / 2).min(table.chips()),
+            Some(false) | None => basic_strategy::bet_insurance(),
+        }
+    }
+
+    fn take_even_money(
+        &mut self,
+        table: TableView,
+        _player_hand: &PlayerHand,
+        _dealer_hand: DealerView,
+    ) -> bool {
+        // Even money is insurance on a blackjack, so it is taken whenever insurance would be
+        let true_count = self.system.true_count(table.shoe());
+        self.index_plays.insurance(true_count) == Some(true)
+    }
+
+    fn switch_cards(
+        &mut self,
+        table: TableView,
+        player_hands: &[PlayerHand],
+        dealer_hand: DealerView,
+    ) -> bool {
+        // There are no index plays for switching, so this follows basic strategy
+        BasicStrategy.switch_cards(table, player_hands, dealer_hand)
+    }
+
+    fn play_hand(
+        &mut self,
+        table: TableView,
+        player_turn: &PlayerTurn,
+        dealer_hand: DealerView,
+    ) -> HandAction {
+        let true_count = self.system.true_count(table.shoe());
+        match self.index_plays.play_hand(true_count, table, player_turn, dealer_hand) {
+            Some(preferred) => basic_strategy::resolve(table, player_turn, preferred),
+            None => basic_strategy::play_hand(table, player_turn, dealer_hand),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::shoe::Shoe;
+    use crate::card::Rank;
+    use crate::game::Table;
+    use crate::rules::{PairDefinition, Rules};
+    use crate::simulation::{Budget, Progress, Simulation};
+    use crate::test_helpers::{card, dealer, hand_of};
+
+    /// A table whose single deck shoe has already dealt the given cards, followed by eights,
+    /// which are neutral in Hi-Lo.
+    fn table_after(dealt: &[Rank], rules: Rules) -> Table {
+        let mut cards: Vec<_> = dealt.iter().cloned().map(card).collect();
+        cards.resize(52, card(Rank::Eight));
+        let mut table = Table::new(1_000, Shoe::stacked(cards), rules);
+        for _ in dealt {
+            table.shoe.draw_card();
+        }
+        table
+    }
+
+    #[test]
+    fn test_parse_index_plays() {
+        let plays = IndexPlays::illustrious_18_and_fab_4();
+        assert_eq!(plays.plays.len(), 23);
+        assert_eq!(plays.insurance(3.0), Some(true));
+        assert_eq!(plays.insurance(2.9), None);
+        assert_eq!(
+            plays.plays[1],
+            IndexPlay {
+                situation: Situation::Play(HandKind::Hard(14), 10),
+                action: IndexAction::Play(PreferredAction::SurrenderOrHit),
+                threshold: Threshold::AtLeast(3.0),
+            }
+        );
+        assert_eq!(
+            "early pair 8 vs 11 decline < -2\nhard 12 vs 4 double-or-hit >= 0"
+                .parse::<IndexPlays>()
+                .map(|plays| plays.plays.len()),
+            Ok(2)
+        );
+        assert_eq!(
+            "insurance stand >= 3".parse::<IndexPlays>(),
+            Err(ParseError::InvalidAction(1))
+        );
+        assert_eq!(
+            "\nsoft 22 vs 10 stand >= 0".parse::<IndexPlays>(),
+            Err(ParseError::InvalidSituation(2))
+        );
+        assert_eq!(
+            "hard 16 vs 10 stand > 0".parse::<IndexPlays>(),
+            Err(ParseError::InvalidThreshold(1))
+        );
+    }
+
+    #[test]
+    fn test_index_strategy_plays_valid_hands() {
+        let simulation = Simulation {
+            rules: Rules {
+                insurance: true,
+                ..Rules::default()
+            },
+            budget: Budget::Rounds(5_000),
+            threads: 1,
+            seed: Some(18),
+            ..Simulation::default()
+        }
+        .with_strategy(IndexStrategy::default());
+        let result = simulation.run(&Progress::new()).unwrap();
+        assert_eq!(result.statistics.turns_played(), 5_000);
+    }
+
+    #[test]
+    fn test_index_strategy_stands_on_16_vs_10_at_positive_counts() {
+        let rules = Rules {
+            late_surrender: false,
+            ..Rules::default()
+        };
+        let mut strategy = IndexStrategy::default();
+        let player_turn = PlayerTurn::from(hand_of([Rank::Ten, Rank::Six].map(card), 10));
+        let dealer_hand = dealer([card(Rank::Ten)]);
+        let mut play = |dealt: &[Rank]| {
+            let table = table_after(dealt, rules.clone());
+            strategy.play_hand(TableView::new(&table), &player_turn, DealerView::new(&dealer_hand))
+        };
+        assert_eq!(play(&[]), HandAction::Stand);
+        assert_eq!(play(&[Rank::Five]), HandAction::Stand);
+        assert_eq!(play(&[Rank::King]), HandAction::Hit);
+    }
+
+    #[test]
+    fn test_index_strategy_takes_insurance_and_surrenders_by_count() {
+        let mut strategy = IndexStrategy::default();
+        let player_hand = hand_of([Rank::Ten, Rank::Nine].map(card), 10);
+        let dealer_hand = dealer([card(Rank::Ace)]);
+        let mut insure = |dealt: &[Rank]| {
+            let table = table_after(dealt, Rules::default());
+            strategy.bet_insurance(TableView::new(&table), &player_hand, DealerView::new(&dealer_hand))
+        };
+        assert_eq!(insure(&[Rank::Two, Rank::Three, Rank::Four]), 5);
+        assert_eq!(insure(&[Rank::Two, Rank::Three]), 0);
+
+        // A ten and a king are a pair of tens only when pairs are of the same value
+        let mut strategy = IndexStrategy::new(
+            CountSystem::HI_LO,
+            "early hard 20 vs 11 surrender >= 2".parse().unwrap(),
+        );
+        let player_hand = hand_of([Rank::Ten, Rank::King].map(card), 10);
+        let mut surrender = |dealt: &[Rank], pair_definition| {
+            let rules = Rules {
+                early_surrender: true,
+                pair_definition,
+                ..Rules::default()
+            };
+            let table = table_after(dealt, rules);
+            strategy.surrender_early(TableView::new(&table), &player_hand, DealerView::new(&dealer_hand))
+        };
+        assert!(surrender(&[Rank::Two, Rank::Three], PairDefinition::SameRank));
+        assert!(!surrender(&[Rank::Two], PairDefinition::SameRank));
+        assert!(!surrender(&[Rank::Two, Rank::Three], PairDefinition::SameValue));
+    }
+}
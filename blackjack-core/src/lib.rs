@@ -2,5 +2,18 @@ pub mod rules;
 pub mod card;
 pub mod state;
 pub mod game;
+pub mod strategy;
 pub mod basic_strategy;
+pub mod analysis;
+pub mod adaptive_strategy;
+pub mod counting;
+pub mod betting;
+pub mod index_plays;
 pub mod statistics;
+pub mod simulation;
+pub mod comparison;
+pub mod multi_seat;
+pub mod side_bets;
+
+#[cfg(test)]
+mod test_helpers;
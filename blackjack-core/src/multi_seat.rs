@@ -0,0 +1,313 @@
+//! Tables with several seats, which are all dealt from the same shoe.
+//! Every seat has its own bankroll, strategy and statistics. The seats are dealt in seat order,
+//! one card at a time as at a real table, and play their hands in seat order against a single
+//! dealer hand. Every player sees all cards which are dealt face up, including other players' cards.
+//!
+//! The seats take turns at a shared single-player `Table`: while a seat is acting, its chips and
+//! statistics are at the table, so strategies see the table exactly as they would when playing alone.
+
+use std::mem;
+
+use crate::card::hand::{DealerHand, Status};
+use crate::card::shoe::Shoe;
+use crate::game::{Error, Input, Table};
+use crate::rules::Rules;
+use crate::state::GameState;
+use crate::statistics::Statistics;
+use crate::strategy::{self, Strategy};
+
+/// The maximum number of seats at a table.
+pub const MAX_SEATS: usize = 7;
+
+/// A seat at a multi-seat table.
+#[derive(Debug, Clone)]
+pub struct Seat<S> {
+    /// The player's chips
+    pub chips: u32,
+    /// Fractions of a chip owed to the player, paid once they add up to a whole chip
+    pub fractional_chips: f64,
+    /// The strategy the player follows
+    pub strategy: S,
+    /// The player's statistics
+    pub statistics: Statistics,
+}
+
+impl<S> Seat<S> {
+    #[must_use]
+    pub const fn new(chips: u32, strategy: S) -> Self {
+        Self {
+            chips,
+            fractional_chips: 0.0,
+            strategy,
+            statistics: Statistics::new(),
+        }
+    }
+}
+
+/// A table with between 1 and 7 seats, which share the shoe, the rules and the dealer.
+#[derive(Debug)]
+pub struct MultiSeatTable<S> {
+    /// The seats, in the order in which they are dealt
+    pub seats: Vec<Seat<S>>,
+    /// The table at which the seats take turns
+    table: Table,
+}
+
+impl<S: Strategy> MultiSeatTable<S> {
+    /// Creates a table with the given seats, which are dealt in the given order.
+    /// # Panics
+    /// Panics if there are no seats or more than `MAX_SEATS` seats, or if the rules are invalid,
+    /// see `Rules::validate`.
+    #[must_use]
+    pub fn new(shoe: Shoe, rules: Rules, seats: Vec<Seat<S>>) -> Self {
+        assert!(
+            (1..=MAX_SEATS).contains(&seats.len()),
+            "a table has between 1 and {MAX_SEATS} seats"
+        );
+        Self {
+            seats,
+            table: Table::new(0, shoe, rules),
+        }
+    }
+
+    /// The shoe shared by all seats.
+    #[must_use]
+    pub const fn shoe(&self) -> &Shoe {
+        &self.table.shoe
+    }
+
+    /// The table rules.
+    #[must_use]
+    pub const fn rules(&self) -> &Rules {
+        &self.table.rules
+    }
+
+    /// Plays a single round with every seat which can afford the minimum bet.
+    /// A seat may bet on several spots, which are dealt one after another.
+    /// Seats which bet nothing sit out, which is recorded in their statistics. If every seat sits
+    /// out, the dealer deals the round to themselves, just like to a single player sitting out at
+    /// a `Table`.
+    /// The shoe is shuffled after the round if necessary.
+    /// Returns the seats which ran out of chips during the round.
+    /// # Errors
+    /// Returns an error if a strategy gave an input which the game could not accept.
+    pub fn play_round(&mut self) -> Result<Vec<usize>, Error> {
+        let mut states = Vec::with_capacity(self.seats.len());
+        for seat in 0..self.seats.len() {
+            let state = self.with_seat(seat, |table, strategy| {
+                if table.is_out_of_chips() {
+                    return Ok(None);
+                }
+                match strategy::input(strategy, table, &GameState::Betting) {
+                    Some(Input::SitOut) => {
+                        table.statistics.sit_out();
+                        Ok(None)
+                    }
+                    input => table.progress(GameState::Betting, input).map(Some),
+                }
+            });
+            states.push(state.map_err(|(_, error)| error)?);
+        }
+        if states.iter().all(Option::is_none) {
+            self.table.deal_dealer_only_round();
+        } else {
+            self.deal(&mut states)?;
+        }
+        let mut out_of_chips = Vec::new();
+        for (seat, state) in states.iter().enumerate() {
+            if state == &Some(GameState::GameOver) {
+                out_of_chips.push(seat);
+            }
+        }
+        if self.table.shoe.needs_shuffle() {
+            self.table.shoe.shuffle();
+        }
+        Ok(out_of_chips)
+    }
+
+    /// Deals the round to the seats which placed a bet, plays their hands and settles them.
+    fn deal(&mut self, states: &mut [Option<GameState>]) -> Result<(), Error> {
+        self.play_until(states, |state| matches!(state, GameState::DealFirstDealerCard { .. }))?;
+        let mut dealer_hand =
+            DealerHand::new(self.table.shoe.draw_card(), self.table.rules.dealer_soft_17);
+        for state in states.iter_mut().flatten() {
+            *state = match mem::take(state) {
+                GameState::DealFirstDealerCard { player_hands } => GameState::DealSecondPlayerCard {
+                    player_hands,
+                    dealer_hand: dealer_hand.clone(),
+                },
+                state => state,
+            };
+        }
+        self.play_until(states, |state| matches!(state, GameState::DealHoleCard { .. }))?;
+        if !self.table.rules.no_hole_card {
+            dealer_hand += self.table.shoe.draw_hole_card();
+        }
+        for state in states.iter_mut().flatten() {
+            *state = match mem::take(state) {
+                GameState::DealHoleCard { player_hands, .. } => {
+                    self.table.start_player_turn(player_hands, dealer_hand.clone())
+                }
+                state => state,
+            };
+        }
+        // Every seat makes its choices before the dealer checks their hole card,
+        // and only then do the seats play their hands one after another
+        self.play_until(states, |state| {
+            matches!(
+                state,
+                GameState::CheckDealerHoleCard { .. }
+                    | GameState::PlayPlayerTurn { .. }
+                    | GameState::RevealHoleCard { .. }
+                    | GameState::RoundOver { .. }
+            )
+        })?;
+        self.play_until(states, |state| {
+            matches!(state, GameState::RevealHoleCard { .. } | GameState::RoundOver { .. })
+        })?;
+        self.play_dealer_turn(states, &mut dealer_hand);
+        for state in states.iter_mut().flatten() {
+            // The round may already be over for some seats, e.g. if they took even money
+            *state = match mem::take(state) {
+                GameState::RevealHoleCard { finished_hands, insurance_bet, .. } => {
+                    GameState::RoundOver {
+                        finished_hands,
+                        dealer_hand: dealer_hand.clone(),
+                        insurance_bet,
+                    }
+                }
+                state => state,
+            };
+        }
+        self.play_until(states, |state| {
+            matches!(
+                state,
+                GameState::Betting | GameState::Shuffle | GameState::GameOver
+            )
+        })
+    }
+
+    /// The dealer plays their hand once every seat has finished playing, unless the round is
+    /// already over for all seats.
+    fn play_dealer_turn(&mut self, states: &[Option<GameState>], dealer_hand: &mut DealerHand) {
+        let waiting = || {
+            states.iter().flatten().filter_map(|state| match state {
+                GameState::RevealHoleCard { finished_hands, insurance_bet, .. } => {
+                    Some((finished_hands, *insurance_bet))
+                }
+                _ => None,
+            })
+        };
+        if waiting().next().is_none() {
+            return;
+        }
+        let insured = waiting().any(|(_, insurance_bet)| insurance_bet > 0);
+        let finished_hands = waiting().flat_map(|(finished_hands, _)| finished_hands);
+        self.table.turn_over_hole_card(dealer_hand, finished_hands, insured);
+        while dealer_hand.status == Status::InPlay {
+            *dealer_hand += self.table.shoe.draw_card();
+        }
+    }
+
+    /// Progresses the state of every seat which is playing this round, in seat order,
+    /// until the seat has to wait for the dealer or the other seats.
+    fn play_until(
+        &mut self,
+        states: &mut [Option<GameState>],
+        waits: fn(&GameState) -> bool,
+    ) -> Result<(), Error> {
+        for (seat, state) in states.iter_mut().enumerate() {
+            let Some(current) = state.take() else {
+                continue;
+            };
+            let next = self.with_seat(seat, |table, strategy| {
+                let mut state = current;
+                while !waits(&state) {
+                    let input = strategy::input(strategy, table, &state);
+                    state = table.progress(state, input)?;
+                }
+                Ok(state)
+            });
+            *state = Some(next.map_err(|(_, error)| error)?);
+        }
+        Ok(())
+    }
+
+    /// Seats the player at the table while they act, so that the table holds their chips and
+    /// statistics, and gets up again afterwards.
+    fn with_seat<T>(&mut self, seat: usize, act: impl FnOnce(&mut Table, &mut S) -> T) -> T {
+        let seat = &mut self.seats[seat];
+        swap_seat(&mut self.table, seat);
+        let result = act(&mut self.table, &mut seat.strategy);
+        swap_seat(&mut self.table, seat);
+        result
+    }
+}
+
+/// Swaps the player's chips and statistics between the seat and the table.
+fn swap_seat<S>(table: &mut Table, seat: &mut Seat<S>) {
+    mem::swap(&mut table.chips, &mut seat.chips);
+    mem::swap(&mut table.fractional_chips, &mut seat.fractional_chips);
+    mem::swap(&mut table.statistics, &mut seat.statistics);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic_strategy::BasicStrategy;
+    use crate::betting::{FlatBet, WithBetting};
+    use crate::simulation::play_round;
+
+    #[test]
+    fn test_single_seat_plays_like_table() {
+        // With one seat, the cards are dealt in the same order as at a single-player table
+        let mut table = Table::new(1_000_000, Shoe::with_seed(6, 0.75, 3), Rules::default());
+        table.fast_forward = true;
+        let seats = vec![Seat::new(1_000_000, BasicStrategy)];
+        let mut multi_seat = MultiSeatTable::new(Shoe::with_seed(6, 0.75, 3), Rules::default(), seats);
+        for _ in 0..2_000 {
+            play_round(&mut table, &mut BasicStrategy).unwrap();
+            multi_seat.play_round().unwrap();
+        }
+        assert_eq!(multi_seat.seats[0].chips, table.chips);
+        assert_eq!(multi_seat.seats[0].statistics.total_won(), table.statistics.total_won());
+    }
+
+    #[test]
+    fn test_seats_sit_out_like_table() {
+        let player = |bet| WithBetting::new(BasicStrategy, FlatBet(bet));
+        let mut table = Table::new(1_000, Shoe::with_seed(6, 0.75, 5), Rules::default());
+        table.fast_forward = true;
+        let seats = vec![Seat::new(1_000, player(0)), Seat::new(1_000, player(100))];
+        let mut multi_seat = MultiSeatTable::new(Shoe::with_seed(6, 0.75, 5), Rules::default(), seats);
+        for _ in 0..10 {
+            play_round(&mut table, &mut player(0)).unwrap();
+            multi_seat.play_round().unwrap();
+        }
+        assert_eq!(table.statistics.rounds_sat_out(), 10);
+        assert_eq!(multi_seat.seats[0].statistics.rounds_sat_out(), 10);
+        assert_eq!(multi_seat.seats[0].statistics.turns_played(), 0);
+        assert_eq!(multi_seat.seats[1].statistics.turns_played(), 10);
+    }
+
+    #[test]
+    fn test_full_table_plays_fewer_rounds_per_shoe() {
+        let rounds_per_shoe = |seats: usize| {
+            let seats = vec![Seat::new(1_000_000, BasicStrategy); seats];
+            let mut table = MultiSeatTable::new(Shoe::with_seed(6, 0.75, 11), Rules::default(), seats);
+            let mut rounds = 0;
+            while table.shoe().cards_drawn > 0 || rounds == 0 {
+                table.play_round().unwrap();
+                rounds += 1;
+            }
+            for seat in &table.seats {
+                assert_eq!(seat.statistics.turns_played(), rounds);
+            }
+            rounds
+        };
+        let alone = rounds_per_shoe(1);
+        let full = rounds_per_shoe(MAX_SEATS);
+        assert!((40..=70).contains(&alone), "{alone} rounds alone");
+        assert!((8..=15).contains(&full), "{full} rounds at a full table");
+    }
+}
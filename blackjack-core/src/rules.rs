@@ -1,5 +1,16 @@
 //! Blackjack table rules.
 
+use std::sync::Arc;
+
+use crate::card::{Card, Rank};
+use crate::side_bets::SideBet;
+
+/// A reason why rules cannot be played.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RulesError {
+    ZeroPayoutDenominator,
+}
+
 /// The action the dealer takes on a soft 17.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DealerSoft17Action {
@@ -7,11 +18,156 @@ pub enum DealerSoft17Action {
     Hit,
 }
 
-/// The payout for a blackjack, either 3:2 or 6:5.
+/// The payout for a blackjack.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BlackjackPayout {
     ThreeToTwo,
-    SixToFive
+    SixToFive,
+    SevenToFive,
+    TwoToOne,
+    OneToOne,
+    /// A custom payout of numerator:denominator. The denominator must not be zero.
+    Custom(u32, u32),
+}
+
+impl BlackjackPayout {
+    /// Creates a custom payout of numerator:denominator.
+    /// # Errors
+    /// Returns an error if the denominator is zero.
+    pub const fn custom(numerator: u32, denominator: u32) -> Result<Self, RulesError> {
+        if denominator == 0 {
+            Err(RulesError::ZeroPayoutDenominator)
+        } else {
+            Ok(Self::Custom(numerator, denominator))
+        }
+    }
+
+    /// Returns the payout as (numerator, denominator), e.g. (3, 2) for 3:2.
+    #[must_use]
+    pub const fn ratio(self) -> (u32, u32) {
+        match self {
+            Self::ThreeToTwo => (3, 2),
+            Self::SixToFive => (6, 5),
+            Self::SevenToFive => (7, 5),
+            Self::TwoToOne => (2, 1),
+            Self::OneToOne => (1, 1),
+            Self::Custom(numerator, denominator) => (numerator, denominator),
+        }
+    }
+}
+
+/// How payouts which are not a whole number of chips are paid, e.g. 3:2 on an odd bet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChipRounding {
+    /// Fractions of a chip are not paid, as at most tables
+    Down,
+    /// Fractions are rounded down to half chips, which are kept until they add up to a whole chip
+    HalfChips,
+    /// Fractions are kept exactly, and paid as soon as they add up to a whole chip
+    Exact,
+}
+
+/// The hands on which players are allowed to double down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoubleRestriction {
+    /// Any two cards
+    AnyTwoCards,
+    /// Two cards totalling hard 9, 10 or 11
+    NineToEleven,
+    /// Two cards totalling hard 10 or 11 (the Reno rule)
+    TenToEleven,
+    /// Any number of cards, so players can also double down after hitting
+    AnyCards,
+}
+
+impl DoubleRestriction {
+    /// Returns whether a hand with the given number of cards and value may be doubled.
+    #[must_use]
+    pub const fn allows(self, cards: usize, total: u8, soft: bool) -> bool {
+        match self {
+            Self::AnyTwoCards => cards == 2,
+            Self::NineToEleven => cards == 2 && !soft && matches!(total, 9..=11),
+            Self::TenToEleven => cards == 2 && !soft && matches!(total, 10 | 11),
+            Self::AnyCards => cards >= 2,
+        }
+    }
+}
+
+/// Which two-card hands are pairs that can be split.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairDefinition {
+    /// Two cards of the same rank, e.g. two kings, but not a jack and a queen
+    SameRank,
+    /// Two cards of the same value, e.g. any two ten-valued cards
+    SameValue,
+}
+
+/// When the dealer checks their hole card for blackjack, before the player plays their hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Peek {
+    /// The dealer peeks when showing an ace or a ten
+    AceOrTen,
+    /// The dealer only peeks when showing an ace
+    AceOnly,
+    /// The dealer never peeks, so a blackjack is only revealed after the player has played
+    Never,
+}
+
+/// A bonus paid on a 21 made of particular cards, depending on their suits.
+/// Payouts are (numerator, denominator), e.g. (3, 2) for 3:2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SuitedBonus {
+    /// The cards are of mixed suits
+    pub mixed: (u32, u32),
+    /// The cards are all of the same suit
+    pub suited: (u32, u32),
+    /// The cards are all spades
+    pub spades: (u32, u32),
+}
+
+/// Bonus payouts for a blackjack whose two cards are of the same suit, instead of the blackjack
+/// payout. Payouts are (numerator, denominator), e.g. (2, 1) for 2:1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SuitedBlackjackBonus {
+    /// The cards are of the same suit
+    pub suited: (u32, u32),
+    /// The cards are both spades
+    pub spades: (u32, u32),
+}
+
+/// Bonuses paid on a player's 21 instead of even money, as in Spanish 21.
+/// Bonuses are not paid on doubled hands, and only the highest bonus is paid.
+/// Payouts are (numerator, denominator), e.g. (3, 2) for 3:2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TwentyOneBonuses {
+    /// 21 with five cards
+    pub five_cards: (u32, u32),
+    /// 21 with six cards
+    pub six_cards: (u32, u32),
+    /// 21 with seven or more cards
+    pub seven_cards: (u32, u32),
+    /// 21 made of a six, a seven and an eight
+    pub six_seven_eight: SuitedBonus,
+    /// 21 made of three sevens
+    pub seven_seven_seven: SuitedBonus,
+}
+
+impl Default for TwentyOneBonuses {
+    /// The bonuses paid in Spanish 21.
+    fn default() -> Self {
+        let three_cards = SuitedBonus {
+            mixed: (3, 2),
+            suited: (2, 1),
+            spades: (3, 1),
+        };
+        Self {
+            five_cards: (3, 2),
+            six_cards: (2, 1),
+            seven_cards: (3, 1),
+            six_seven_eight: three_cards,
+            seven_seven_seven: three_cards,
+        }
+    }
 }
 
 /// Blackjack table rules.
@@ -21,8 +177,15 @@ pub struct Rules {
     pub max_bet: Option<u32>,
     /// The minimum bet allowed, if any.
     pub min_bet: Option<u32>,
+    /// The maximum number of spots a player can bet on in a round (at least 1).
+    pub max_spots: u8,
     /// The payout for a blackjack.
     pub blackjack_payout: BlackjackPayout,
+    /// How payouts which are not a whole number of chips are paid.
+    pub chip_rounding: ChipRounding,
+    /// Whether a player with blackjack is offered even money when the dealer shows an ace.
+    /// Taking even money pays the blackjack 1:1, even if the dealer also has blackjack.
+    pub even_money: bool,
     /// The action the dealer takes on a soft 17.
     pub dealer_soft_17: DealerSoft17Action,
     /// Whether to offer insurance.
@@ -31,12 +194,67 @@ pub struct Rules {
     pub early_surrender: bool,
     /// Whether players are allowed to surrender after the dealer checks for blackjack.
     pub late_surrender: bool,
+    /// The hands on which players can double down.
+    pub double_restriction: DoubleRestriction,
+    /// Which two-card hands are pairs that can be split.
+    pub pair_definition: PairDefinition,
     /// The maximum number of times a player can split a hand.
     pub max_splits: Option<u8>,
+    /// The maximum number of times a player can split pairs of each rank, in addition to the
+    /// overall maximum, indexed by rank (so tens are at index 8 and aces at index 12).
+    /// For example, a limit of 1 means a pair of that rank cannot be resplit.
+    /// If pairs are defined by value, the limit of the first card of the pair applies.
+    pub max_splits_by_rank: [Option<u8>; 13],
     /// Whether players can double down on a split hand.
     pub double_after_split: bool,
     /// Whether players can split aces.
     pub split_aces: bool,
+    /// Whether players can split aces again if a split ace is dealt another ace.
+    pub resplit_aces: bool,
+    /// Whether players can hit split aces.
+    pub hit_split_aces: bool,
+    /// Whether split aces receive only one card each.
+    /// Split aces cannot be hit or doubled and stand automatically, unless they are resplit.
+    pub split_aces_one_card: bool,
+    /// Whether a split hand of 21 with two cards counts as a blackjack, rather than just 21.
+    pub split_21_is_blackjack: bool,
+    /// Whether the dealer plays without a hole card (European no-hole-card rules).
+    /// The dealer only draws their second card after the player has finished playing,
+    /// so the player may lose doubled and split bets to a dealer blackjack.
+    pub no_hole_card: bool,
+    /// When the dealer checks their hole card for blackjack. Without a hole card, they never do.
+    /// If the dealer does not peek, the player may lose doubled and split bets to a dealer blackjack.
+    pub peek: Peek,
+    /// Whether the player only loses their original bet to a dealer blackjack which is revealed
+    /// after the player has doubled or split. Additional bets are returned.
+    pub original_bets_only: bool,
+    /// Whether a player's 21 always wins, even against a dealer's 21,
+    /// and a player's blackjack beats a dealer's blackjack.
+    pub player_21_always_wins: bool,
+    /// The bonuses paid on special hands of 21, if any.
+    pub twenty_one_bonuses: Option<TwentyOneBonuses>,
+    /// The bonus paid on a suited blackjack, if any.
+    pub suited_blackjack_bonus: Option<SuitedBlackjackBonus>,
+    /// The number of cards with which a player hand wins automatically if it has not bust,
+    /// e.g. 5 for a five-card Charlie. A Charlie still loses to a dealer blackjack.
+    pub charlie: Option<u8>,
+    /// Whether players can surrender a hand after doubling down on it, also known as double-down
+    /// rescue. The player forfeits their original bet, and gets back the amount they doubled.
+    pub double_down_rescue: bool,
+    /// Whether a dealer hand which busts with 22 pushes against all player hands which are not
+    /// bust, rather than losing. Player blackjacks still win.
+    pub dealer_22_pushes: bool,
+    /// Whether the player plays two hands and may switch their second cards, as in Blackjack Switch.
+    /// The player must bet the same amount on exactly two spots.
+    pub switch: bool,
+    /// Whether doubling down on a hard 9, 10 or 11 with two cards is free, as in Free Bet blackjack.
+    /// The house puts up the double, so the player only risks their original bet but is paid on both.
+    pub free_doubles: bool,
+    /// Whether splitting pairs other than ten-valued cards is free, as in Free Bet blackjack.
+    /// The house puts up the bet on the new hand, which is only paid if the hand wins.
+    pub free_splits: bool,
+    /// The side bets offered at the table, which players can place next to their bet on each spot.
+    pub side_bets: Vec<Arc<dyn SideBet>>,
 }
 
 impl Default for Rules {
@@ -44,14 +262,150 @@ impl Default for Rules {
         Self {
             max_bet: None,
             min_bet: Some(100),
+            max_spots: 3,
             blackjack_payout: BlackjackPayout::ThreeToTwo,
+            chip_rounding: ChipRounding::Down,
+            even_money: false,
             dealer_soft_17: DealerSoft17Action::Stand,
             insurance: false,
             early_surrender: false,
             late_surrender: true,
+            double_restriction: DoubleRestriction::AnyTwoCards,
+            pair_definition: PairDefinition::SameRank,
             max_splits: Some(5),
+            max_splits_by_rank: [None; 13],
             double_after_split: true,
             split_aces: true,
+            resplit_aces: true,
+            hit_split_aces: true,
+            split_aces_one_card: false,
+            split_21_is_blackjack: false,
+            no_hole_card: false,
+            peek: Peek::AceOrTen,
+            original_bets_only: false,
+            player_21_always_wins: false,
+            twenty_one_bonuses: None,
+            suited_blackjack_bonus: None,
+            charlie: None,
+            double_down_rescue: false,
+            dealer_22_pushes: false,
+            switch: false,
+            free_doubles: false,
+            free_splits: false,
+            side_bets: Vec::new(),
+        }
+    }
+}
+
+impl Rules {
+    /// The rules of Spanish 21, which is dealt from a shoe without tens (but with face cards).
+    /// Players can double on any number of cards and surrender late, even after doubling,
+    /// their 21s always win and special 21s pay bonuses.
+    #[must_use]
+    pub fn spanish_21() -> Self {
+        Self {
+            dealer_soft_17: DealerSoft17Action::Hit,
+            late_surrender: true,
+            double_restriction: DoubleRestriction::AnyCards,
+            max_splits: Some(3),
+            resplit_aces: true,
+            player_21_always_wins: true,
+            twenty_one_bonuses: Some(TwentyOneBonuses::default()),
+            double_down_rescue: true,
+            ..Self::default()
+        }
+    }
+
+    /// The rules of Blackjack Switch, where the player plays two hands and may switch their second
+    /// cards. To make up for it, blackjack only pays 1:1 and a dealer 22 pushes.
+    #[must_use]
+    pub fn blackjack_switch() -> Self {
+        Self {
+            max_spots: 2,
+            blackjack_payout: BlackjackPayout::OneToOne,
+            dealer_soft_17: DealerSoft17Action::Hit,
+            late_surrender: false,
+            max_splits: Some(3),
+            resplit_aces: false,
+            hit_split_aces: false,
+            split_aces_one_card: true,
+            dealer_22_pushes: true,
+            switch: true,
+            ..Self::default()
         }
     }
-}
\ No newline at end of file
+
+    /// The rules of Free Bet blackjack, where the house pays for doubling down on a hard 9, 10 or 11
+    /// and for splitting pairs other than tens. To make up for it, a dealer 22 pushes.
+    #[must_use]
+    pub fn free_bet() -> Self {
+        Self {
+            dealer_soft_17: DealerSoft17Action::Hit,
+            late_surrender: false,
+            max_splits: Some(3),
+            resplit_aces: false,
+            hit_split_aces: false,
+            split_aces_one_card: true,
+            dealer_22_pushes: true,
+            free_doubles: true,
+            free_splits: true,
+            ..Self::default()
+        }
+    }
+
+    /// Returns whether the dealer checks for blackjack before the player plays, when showing the
+    /// given card value (2-11). Otherwise, a dealer blackjack is only revealed at the end of the round.
+    #[must_use]
+    pub const fn dealer_peeks(&self, up_card: u8) -> bool {
+        !self.no_hole_card
+            && match self.peek {
+                Peek::AceOrTen => up_card >= 10,
+                Peek::AceOnly => up_card == 11,
+                Peek::Never => false,
+            }
+    }
+
+    /// Checks that the rules can be played, i.e. that every payout has a non-zero denominator.
+    /// # Errors
+    /// Returns the first problem found with the rules.
+    pub fn validate(&self) -> Result<(), RulesError> {
+        let mut ratios = vec![self.blackjack_payout.ratio()];
+        if let Some(bonus) = &self.suited_blackjack_bonus {
+            ratios.extend([bonus.suited, bonus.spades]);
+        }
+        if let Some(bonuses) = &self.twenty_one_bonuses {
+            ratios.extend([bonuses.five_cards, bonuses.six_cards, bonuses.seven_cards]);
+            for bonus in [bonuses.six_seven_eight, bonuses.seven_seven_seven] {
+                ratios.extend([bonus.mixed, bonus.suited, bonus.spades]);
+            }
+        }
+        if ratios.iter().any(|&(_, denominator)| denominator == 0) {
+            Err(RulesError::ZeroPayoutDenominator)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the maximum number of times a player can split pairs of the given rank,
+    /// taking both the overall and the per-rank limits into account.
+    #[must_use]
+    pub fn max_splits_of(&self, rank: &Rank) -> Option<u8> {
+        match (self.max_splits, self.max_splits_by_rank[rank.clone() as usize]) {
+            (Some(max), Some(max_by_rank)) => Some(max.min(max_by_rank)),
+            (max, max_by_rank) => max.or(max_by_rank),
+        }
+    }
+
+    /// Returns the maximum number of times a player can split pairs of the given card value (2-11),
+    /// which is the most lenient limit of the ranks worth that value, e.g. for analysing hands
+    /// by value where the ranks of ten-valued cards are not known.
+    #[must_use]
+    pub fn max_splits_of_value(&self, value: u8) -> Option<u8> {
+        (0..13)
+            .map(|rank| Card::from_ordinal(rank * 4).rank)
+            .filter(|rank| rank.worth() == value)
+            .map(|rank| self.max_splits_of(&rank))
+            .reduce(|a, b| a.zip(b).map(|(a, b)| a.max(b)))
+            .flatten()
+    }
+}
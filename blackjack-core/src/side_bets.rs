@@ -0,0 +1,465 @@
+//! Side bets, which are placed next to the main bet on a spot and settled independently of it.
+//! A side bet is settled against the player's first two cards on the spot and the dealer's hand
+//! at the end of the round. The side bets offered at a table are part of the table rules, and each
+//! of them pays according to its paytable.
+
+use std::fmt;
+
+use crate::card::hand::{DealerHand, PlayerHand, PlayerTurn, Status};
+use crate::card::{Card, Rank, Suit};
+use crate::game::HandAction;
+use crate::strategy::{DealerView, Strategy, TableView};
+
+/// A side bet offered at the table.
+/// Payouts are given as odds, e.g. 5.0 for 5:1, and the bet is returned along with the payout.
+pub trait SideBet: fmt::Debug + Send + Sync {
+    /// The name of the side bet.
+    fn name(&self) -> &'static str;
+
+    /// Whether the side bet depends on the dealer's final hand. If so, the dealer completes their
+    /// hand even if the player has no hand left which could beat it.
+    fn needs_dealer_hand(&self) -> bool {
+        false
+    }
+
+    /// Returns the odds the side bet pays, or None if it loses.
+    fn payout(&self, player_cards: &[Card], dealer_hand: &DealerHand) -> Option<f64>;
+}
+
+/// The side bets placed on a spot, and the player's first two cards on the spot which they are
+/// settled against.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpotSideBets {
+    /// The bet on each side bet offered at the table, in the order of the rules (0 if not placed)
+    pub bets: Vec<u32>,
+    /// The player's first two cards on the spot, even if the hand was split afterwards
+    pub player_cards: Vec<Card>,
+}
+
+impl SpotSideBets {
+    /// Returns the bets which were placed, along with the index of the side bet in the rules.
+    pub fn placed(&self) -> impl Iterator<Item = (usize, u32)> + '_ {
+        self.bets.iter().copied().enumerate().filter(|&(_, bet)| bet > 0)
+    }
+
+    /// The total amount bet on side bets.
+    #[must_use]
+    pub fn total(&self) -> u32 {
+        self.bets.iter().sum()
+    }
+}
+
+/// Perfect Pairs pays if the player's first two cards are a pair of the same rank.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PerfectPairs {
+    /// A pair of different colours, e.g. a red and a black eight
+    pub mixed_pair: f64,
+    /// A pair of the same colour, but different suits
+    pub coloured_pair: f64,
+    /// A pair of the same suit
+    pub perfect_pair: f64,
+}
+
+impl Default for PerfectPairs {
+    fn default() -> Self {
+        Self {
+            mixed_pair: 6.0,
+            coloured_pair: 12.0,
+            perfect_pair: 25.0,
+        }
+    }
+}
+
+impl SideBet for PerfectPairs {
+    fn name(&self) -> &'static str {
+        "Perfect Pairs"
+    }
+
+    fn payout(&self, player_cards: &[Card], _dealer_hand: &DealerHand) -> Option<f64> {
+        let [first, second] = player_cards else {
+            return None;
+        };
+        if first.rank != second.rank {
+            None
+        } else if first.suit == second.suit {
+            Some(self.perfect_pair)
+        } else if is_red(&first.suit) == is_red(&second.suit) {
+            Some(self.coloured_pair)
+        } else {
+            Some(self.mixed_pair)
+        }
+    }
+}
+
+/// 21+3 pays if the player's first two cards and the dealer's up card make a three-card poker hand.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TwentyOnePlusThree {
+    /// Three cards of the same suit
+    pub flush: f64,
+    /// Three consecutive ranks, with aces high or low
+    pub straight: f64,
+    /// Three cards of the same rank
+    pub three_of_a_kind: f64,
+    /// Three consecutive ranks of the same suit
+    pub straight_flush: f64,
+    /// Three cards of the same rank and suit
+    pub suited_trips: f64,
+}
+
+impl Default for TwentyOnePlusThree {
+    fn default() -> Self {
+        Self {
+            flush: 5.0,
+            straight: 10.0,
+            three_of_a_kind: 30.0,
+            straight_flush: 40.0,
+            suited_trips: 100.0,
+        }
+    }
+}
+
+impl SideBet for TwentyOnePlusThree {
+    fn name(&self) -> &'static str {
+        "21+3"
+    }
+
+    fn payout(&self, player_cards: &[Card], dealer_hand: &DealerHand) -> Option<f64> {
+        let [first, second] = player_cards else {
+            return None;
+        };
+        let cards = [first, second, dealer_hand.up_card()];
+        let flush = cards.iter().all(|card| card.suit == first.suit);
+        let trips = cards.iter().all(|card| card.rank == first.rank);
+        let mut ranks = cards.map(|card| rank_order(&card.rank));
+        ranks.sort_unstable();
+        // Aces are high, but also count as low in A-2-3
+        let straight = ranks == [ranks[0], ranks[0] + 1, ranks[0] + 2] || ranks == [0, 1, 12];
+        match (trips, straight, flush) {
+            (true, _, true) => Some(self.suited_trips),
+            (_, true, true) => Some(self.straight_flush),
+            (true, _, _) => Some(self.three_of_a_kind),
+            (_, true, _) => Some(self.straight),
+            (_, _, true) => Some(self.flush),
+            _ => None,
+        }
+    }
+}
+
+/// Lucky Ladies pays if the player's first two cards total 20.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LuckyLadies {
+    /// Any two cards totalling 20
+    pub any_20: f64,
+    /// Two suited cards totalling 20
+    pub suited_20: f64,
+    /// Two cards of the same rank and suit totalling 20
+    pub matched_20: f64,
+    /// A pair of queens of hearts
+    pub queens_of_hearts: f64,
+    /// A pair of queens of hearts, and the dealer has blackjack
+    pub queens_of_hearts_dealer_blackjack: f64,
+}
+
+impl Default for LuckyLadies {
+    fn default() -> Self {
+        Self {
+            any_20: 4.0,
+            suited_20: 10.0,
+            matched_20: 25.0,
+            queens_of_hearts: 200.0,
+            queens_of_hearts_dealer_blackjack: 1000.0,
+        }
+    }
+}
+
+impl SideBet for LuckyLadies {
+    fn name(&self) -> &'static str {
+        "Lucky Ladies"
+    }
+
+    /// Without a hole card, the dealer needs their second card to tell whether they have blackjack.
+    fn needs_dealer_hand(&self) -> bool {
+        true
+    }
+
+    fn payout(&self, player_cards: &[Card], dealer_hand: &DealerHand) -> Option<f64> {
+        let [first, second] = player_cards else {
+            return None;
+        };
+        if first.rank.worth() + second.rank.worth() != 20 {
+            return None;
+        }
+        let queen_of_hearts = |card: &Card| card.rank == Rank::Queen && card.suit == Suit::Hearts;
+        if queen_of_hearts(first) && queen_of_hearts(second) {
+            if dealer_hand.status == Status::Blackjack {
+                Some(self.queens_of_hearts_dealer_blackjack)
+            } else {
+                Some(self.queens_of_hearts)
+            }
+        } else if first == second {
+            Some(self.matched_20)
+        } else if first.suit == second.suit {
+            Some(self.suited_20)
+        } else {
+            Some(self.any_20)
+        }
+    }
+}
+
+/// Buster pays if the dealer busts, more the more cards the dealer's bust hand has.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Buster {
+    /// The odds paid for a dealer bust with 3, 4, 5, 6, 7 and 8 or more cards
+    pub by_cards: [f64; 6],
+}
+
+impl Default for Buster {
+    fn default() -> Self {
+        Self {
+            by_cards: [2.0, 2.0, 4.0, 12.0, 50.0, 250.0],
+        }
+    }
+}
+
+impl SideBet for Buster {
+    fn name(&self) -> &'static str {
+        "Buster"
+    }
+
+    fn needs_dealer_hand(&self) -> bool {
+        true
+    }
+
+    fn payout(&self, _player_cards: &[Card], dealer_hand: &DealerHand) -> Option<f64> {
+        (dealer_hand.status == Status::Bust)
+            .then(|| self.by_cards[dealer_hand.size().clamp(3, 8) - 3])
+    }
+}
+
+/// Royal Match pays if the player's first two cards are suited.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoyalMatch {
+    /// Two suited cards
+    pub suited: f64,
+    /// A suited king and queen
+    pub royal_match: f64,
+}
+
+impl Default for RoyalMatch {
+    fn default() -> Self {
+        Self {
+            suited: 2.5,
+            royal_match: 25.0,
+        }
+    }
+}
+
+impl SideBet for RoyalMatch {
+    fn name(&self) -> &'static str {
+        "Royal Match"
+    }
+
+    fn payout(&self, player_cards: &[Card], _dealer_hand: &DealerHand) -> Option<f64> {
+        let [first, second] = player_cards else {
+            return None;
+        };
+        if first.suit != second.suit {
+            None
+        } else if matches!(
+            (&first.rank, &second.rank),
+            (Rank::King, Rank::Queen) | (Rank::Queen, Rank::King)
+        ) {
+            Some(self.royal_match)
+        } else {
+            Some(self.suited)
+        }
+    }
+}
+
+const fn is_red(suit: &Suit) -> bool {
+    matches!(suit, Suit::Hearts | Suit::Diamonds)
+}
+
+/// The position of the rank from two (0) to ace (12).
+const fn rank_order(rank: &Rank) -> u8 {
+    match rank {
+        Rank::Two => 0,
+        Rank::Three => 1,
+        Rank::Four => 2,
+        Rank::Five => 3,
+        Rank::Six => 4,
+        Rank::Seven => 5,
+        Rank::Eight => 6,
+        Rank::Nine => 7,
+        Rank::Ten => 8,
+        Rank::Jack => 9,
+        Rank::Queen => 10,
+        Rank::King => 11,
+        Rank::Ace => 12,
+    }
+}
+
+/// A strategy which places the same side bets on every spot it plays,
+/// and otherwise plays like the given strategy. Side bets the table does not offer are not placed.
+#[derive(Debug, Clone)]
+pub struct WithSideBets<S> {
+    pub strategy: S,
+    /// The bet on each side bet offered at the table, in the order of the rules
+    pub side_bets: Vec<u32>,
+}
+
+impl<S> WithSideBets<S> {
+    #[must_use]
+    pub const fn new(strategy: S, side_bets: Vec<u32>) -> Self {
+        Self {
+            strategy,
+            side_bets,
+        }
+    }
+}
+
+impl<S: Strategy> Strategy for WithSideBets<S> {
+    fn bet(&mut self, table: TableView) -> u32 {
+        self.strategy.bet(table)
+    }
+
+    fn bet_spots(&mut self, table: TableView) -> Vec<u32> {
+        self.strategy.bet_spots(table)
+    }
+
+    fn side_bets(&mut self, table: TableView) -> Vec<u32> {
+        self.side_bets.iter().copied().take(table.rules().side_bets.len()).collect()
+    }
+
+    fn surrender_early(
+        &mut self,
+        table: TableView,
+        player_hand: &PlayerHand,
+        dealer_hand: DealerView,
+    ) -> bool {
+        self.strategy.surrender_early(table, player_hand, dealer_hand)
+    }
+
+    fn bet_insurance(
+        &mut self,
+        table: TableView,
+        player_hand: &PlayerHand,
+        dealer_hand: DealerView,
+    ) -> u32 {
+        self.strategy.bet_insurance(table, player_hand, dealer_hand)
+    }
+
+    fn take_even_money(
+        &mut self,
+        table: TableView,
+        player_hand: &PlayerHand,
+        dealer_hand: DealerView,
+    ) -> bool {
+        self.strategy.take_even_money(table, player_hand, dealer_hand)
+    }
+
+    fn switch_cards(
+        &mut self,
+        table: TableView,
+        player_hands: &[PlayerHand],
+        dealer_hand: DealerView,
+    ) -> bool {
+        self.strategy.switch_cards(table, player_hands, dealer_hand)
+    }
+
+    fn play_hand(
+        &mut self,
+        table: TableView,
+        player_turn: &PlayerTurn,
+        dealer_hand: DealerView,
+    ) -> HandAction {
+        self.strategy.play_hand(table, player_turn, dealer_hand)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::basic_strategy::BasicStrategy;
+    use crate::card::shoe::Shoe;
+    use crate::game::Table;
+    use crate::rules::Rules;
+    use crate::simulation::play_round;
+    use crate::test_helpers::{dealer, suited};
+
+    #[test]
+    fn test_paytables() {
+        let mut dealer_hand = dealer([suited(Rank::Jack, Suit::Hearts)]);
+        let perfect_pairs = PerfectPairs::default();
+        let pair = |suit| [suited(Rank::Eight, Suit::Hearts), suited(Rank::Eight, suit)];
+        assert_eq!(perfect_pairs.payout(&pair(Suit::Hearts), &dealer_hand), Some(25.0));
+        assert_eq!(perfect_pairs.payout(&pair(Suit::Diamonds), &dealer_hand), Some(12.0));
+        assert_eq!(perfect_pairs.payout(&pair(Suit::Clubs), &dealer_hand), Some(6.0));
+
+        let twenty_one_plus_three = TwentyOnePlusThree::default();
+        let straight = [suited(Rank::Queen, Suit::Hearts), suited(Rank::King, Suit::Clubs)];
+        assert_eq!(twenty_one_plus_three.payout(&straight, &dealer_hand), Some(10.0));
+        let flush = [suited(Rank::Two, Suit::Hearts), suited(Rank::Nine, Suit::Hearts)];
+        assert_eq!(twenty_one_plus_three.payout(&flush, &dealer_hand), Some(5.0));
+
+        let lucky_ladies = LuckyLadies::default();
+        let queens = [suited(Rank::Queen, Suit::Hearts), suited(Rank::Queen, Suit::Hearts)];
+        assert_eq!(lucky_ladies.payout(&queens, &dealer_hand), Some(200.0));
+        assert_eq!(lucky_ladies.payout(&straight, &dealer_hand), Some(4.0));
+        assert_eq!(RoyalMatch::default().payout(&flush, &dealer_hand), Some(2.5));
+
+        dealer_hand += suited(Rank::Six, Suit::Spades);
+        dealer_hand += suited(Rank::Nine, Suit::Spades);
+        assert_eq!(Buster::default().payout(&flush, &dealer_hand), Some(2.0));
+    }
+
+    #[test]
+    fn test_side_bets_are_settled_separately() {
+        let rules = Rules {
+            side_bets: vec![Arc::new(Buster::default()), Arc::new(PerfectPairs::default())],
+            ..Rules::default()
+        };
+        let mut table = Table::new(1_000_000, Shoe::with_seed(6, 0.75, 7), rules);
+        table.fast_forward = true;
+        let mut strategy = WithSideBets::new(BasicStrategy, vec![10, 5]);
+        for _ in 0..10_000 {
+            play_round(&mut table, &mut strategy).unwrap();
+        }
+        let statistics = &table.statistics;
+        let [buster, perfect_pairs] = statistics.side_bets() else {
+            panic!("both side bets should have been placed");
+        };
+        assert_eq!(buster.bets_placed(), 10_000);
+        assert_eq!(buster.total_bet(), 100_000);
+        assert_eq!(perfect_pairs.total_bet(), 50_000);
+        // The dealer completes their hand for the Buster bet, even if the player busts
+        assert_eq!(buster.wins(), statistics.dealer_busts());
+        let side_bet_result = (buster.total_won() + perfect_pairs.total_won()) as i64 - 150_000;
+        let main_result = statistics.total_won() as i64 - statistics.total_bet() as i64;
+        assert_eq!(i64::from(table.chips), 1_000_000 + main_result + side_bet_result);
+    }
+
+    #[test]
+    fn test_side_bets_not_offered_are_ignored() {
+        let rules = Rules {
+            side_bets: vec![Arc::new(PerfectPairs::default())],
+            ..Rules::default()
+        };
+        let mut table = Table::new(1_000_000, Shoe::with_seed(6, 0.75, 7), rules);
+        table.fast_forward = true;
+        let mut strategy = WithSideBets::new(BasicStrategy, vec![5, 10]);
+        for _ in 0..1_000 {
+            play_round(&mut table, &mut strategy).unwrap();
+        }
+        let statistics = &table.statistics;
+        let [perfect_pairs] = statistics.side_bets() else {
+            panic!("only the offered side bet should have been placed");
+        };
+        assert_eq!(perfect_pairs.total_bet(), 5_000);
+        let side_bet_result = perfect_pairs.total_won() as i64 - 5_000;
+        let main_result = statistics.total_won() as i64 - statistics.total_bet() as i64;
+        assert_eq!(i64::from(table.chips), 1_000_000 + main_result + side_bet_result);
+    }
+}
@@ -0,0 +1,416 @@
+//! Headless simulation of many rounds of blackjack.
+//! The rounds are spread across several worker threads, each of which plays at its own table.
+//! The statistics of all tables are merged into a single result at the end.
+//! A table may have several seats sharing the shoe, in which case the statistics of each seat
+//! are also kept separately.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+use rand::random;
+
+use crate::basic_strategy::BasicStrategy;
+use crate::card::shoe::{Shoe, ShuffleModel};
+use crate::card::{Card, Rank};
+use crate::game::{Error, Table};
+use crate::multi_seat::{MultiSeatTable, Seat};
+use crate::rules::Rules;
+use crate::state::GameState;
+use crate::statistics::Statistics;
+use crate::strategy::{self, Strategy};
+
+/// The number of rounds a worker plays before reporting its progress.
+pub(crate) const PROGRESS_INTERVAL: usize = 1000;
+
+/// The number of rounds in a row every player may sit out before the budget counts as used up,
+/// so that a strategy which stops betting cannot keep a simulation running forever.
+pub const MAX_ROUNDS_SAT_OUT_IN_A_ROW: usize = 100_000;
+
+/// How long a simulation should run for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Budget {
+    /// Play this many rounds in total.
+    Rounds(usize),
+    /// Play until at least this many hands (including split hands) have been played in total.
+    Hands(usize),
+}
+
+impl Budget {
+    /// Splits the budget evenly between the given number of workers.
+    /// The first workers take the remainder, so the total budget is preserved.
+    pub(crate) fn share(self, worker: usize, workers: usize) -> Self {
+        let split = |total: usize| total / workers + usize::from(worker < total % workers);
+        match self {
+            Self::Rounds(rounds) => Self::Rounds(split(rounds)),
+            Self::Hands(hands) => Self::Hands(split(hands)),
+        }
+    }
+
+    /// Returns whether the budget has been used up by the given statistics, e.g. of all seats at a table.
+    /// It also counts as used up once every player has sat out [`MAX_ROUNDS_SAT_OUT_IN_A_ROW`] rounds.
+    pub(crate) fn is_exhausted<'a>(self, statistics: impl IntoIterator<Item = &'a Statistics> + Clone) -> bool {
+        let sat_out = |statistics: &Statistics| {
+            statistics.rounds_sat_out_in_a_row() >= MAX_ROUNDS_SAT_OUT_IN_A_ROW
+        };
+        if statistics.clone().into_iter().all(sat_out) {
+            return true;
+        }
+        let statistics = statistics.into_iter();
+        match self {
+            Self::Rounds(rounds) => statistics.map(Statistics::turns_played).sum::<usize>() >= rounds,
+            Self::Hands(hands) => statistics.map(Statistics::hands_played).sum::<usize>() >= hands,
+        }
+    }
+}
+
+/// Shared progress of a running simulation.
+/// This can be observed from another thread while the simulation is running,
+/// and can also be used to cancel the simulation early.
+#[derive(Debug, Default)]
+pub struct Progress {
+    /// The number of rounds played so far across all workers.
+    rounds_played: AtomicUsize,
+    /// Whether the simulation should stop as soon as possible.
+    cancelled: AtomicBool,
+}
+
+impl Progress {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            rounds_played: AtomicUsize::new(0),
+            cancelled: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the number of rounds played so far across all workers and seats,
+    /// including rounds sat out.
+    /// Workers report their progress periodically, so this may lag behind slightly.
+    #[must_use]
+    pub fn rounds_played(&self) -> usize {
+        self.rounds_played.load(Ordering::Relaxed)
+    }
+
+    /// Requests the simulation to stop.
+    /// Workers finish the round they are playing and return the statistics gathered so far.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether the simulation has been cancelled.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn report(&self, rounds: usize) {
+        self.rounds_played.fetch_add(rounds, Ordering::Relaxed);
+    }
+}
+
+/// The configuration of a headless simulation.
+/// Every worker plays with its own clone of the strategy.
+#[derive(Debug, Clone)]
+pub struct Simulation<S = BasicStrategy> {
+    /// The table rules
+    pub rules: Rules,
+    /// The number of decks in the shoe
+    pub decks: u8,
+    /// The proportion of cards to play before shuffling
+    pub penetration: f32,
+    /// When the shoe is shuffled
+    pub shuffle_model: ShuffleModel,
+    /// The number of cards burnt after every shuffle
+    pub burn_cards: u8,
+    /// The ranks removed from every deck in the shoe, e.g. the tens in Spanish 21
+    pub removed_ranks: Vec<Rank>,
+    /// The exact cards in the shoe, e.g. a stripped deck or a shoe with extra aces, if the shoe
+    /// is not made of full decks. The shoe still counts as the given number of decks.
+    pub cards: Option<Vec<Card>>,
+    /// The chips each seat starts with. A seat that runs out of chips is given a fresh bankroll.
+    pub chips: u32,
+    /// The strategy the player follows
+    pub strategy: S,
+    /// The number of seats at each table (1-7), which are all played with the same strategy.
+    /// Only the seats themselves are dealt, so a seat sitting out only sees the cards of the other
+    /// seats, or of the dealer's own hand if every seat sits out.
+    pub seats: usize,
+    /// How long to run the simulation for, counting the rounds or hands of all seats
+    pub budget: Budget,
+    /// The number of worker threads to use
+    pub threads: usize,
+    /// The seed from which the seeds of all shoes are derived.
+    /// If None, the shoes are seeded randomly and the simulation is not reproducible.
+    pub seed: Option<u64>,
+}
+
+impl Default for Simulation<BasicStrategy> {
+    fn default() -> Self {
+        Self {
+            rules: Rules::default(),
+            decks: 6,
+            penetration: 0.75,
+            shuffle_model: ShuffleModel::FixedCutCard,
+            burn_cards: 0,
+            removed_ranks: Vec::new(),
+            cards: None,
+            chips: 1_000_000,
+            strategy: BasicStrategy,
+            seats: 1,
+            budget: Budget::Rounds(1_000_000),
+            threads: thread::available_parallelism().map_or(1, usize::from),
+            seed: None,
+        }
+    }
+}
+
+/// The result of a simulation.
+#[derive(Debug, Default, Clone)]
+pub struct SimulationResult {
+    /// The merged statistics of all tables and seats
+    pub statistics: Statistics,
+    /// The merged statistics of each seat across all tables, in seat order
+    pub seat_statistics: Vec<Statistics>,
+    /// The number of rounds dealt at all tables, including rounds in which every seat sat out
+    pub rounds_dealt: usize,
+    /// The number of times a shoe was shuffled
+    pub shuffles: usize,
+    /// The number of times a seat ran out of chips and had to be given a fresh bankroll
+    pub bankruptcies: usize,
+    /// Whether the simulation was cancelled before the budget was used up
+    pub cancelled: bool,
+}
+
+impl SimulationResult {
+    /// Merge the result of another worker into this result.
+    pub fn merge(&mut self, other: &Self) {
+        self.statistics.merge(&other.statistics);
+        if self.seat_statistics.len() < other.seat_statistics.len() {
+            self.seat_statistics.resize_with(other.seat_statistics.len(), Statistics::new);
+        }
+        for (seat, statistics) in self.seat_statistics.iter_mut().zip(&other.seat_statistics) {
+            seat.merge(statistics);
+        }
+        self.rounds_dealt += other.rounds_dealt;
+        self.shuffles += other.shuffles;
+        self.bankruptcies += other.bankruptcies;
+        self.cancelled |= other.cancelled;
+    }
+
+    /// The average number of rounds dealt from a shoe before it was shuffled.
+    #[must_use]
+    pub fn rounds_per_shoe(&self) -> f64 {
+        self.rounds_dealt as f64 / self.shuffles.max(1) as f64
+    }
+}
+
+impl<S> Simulation<S> {
+    /// Returns the same simulation with the player following the given strategy instead.
+    #[must_use]
+    pub fn with_strategy<T>(self, strategy: T) -> Simulation<T> {
+        Simulation {
+            rules: self.rules,
+            decks: self.decks,
+            penetration: self.penetration,
+            shuffle_model: self.shuffle_model,
+            burn_cards: self.burn_cards,
+            removed_ranks: self.removed_ranks,
+            cards: self.cards,
+            chips: self.chips,
+            strategy,
+            seats: self.seats,
+            budget: self.budget,
+            threads: self.threads,
+            seed: self.seed,
+        }
+    }
+}
+
+impl<S: Strategy + Clone + Send + Sync> Simulation<S> {
+    /// Runs the simulation, blocking until it is finished or cancelled.
+    /// # Errors
+    /// Returns an error if the rules are invalid, or the strategy gave an input which the game
+    /// could not accept.
+    /// # Panics
+    /// Panics if the number of threads is 0 or a worker thread panics.
+    pub fn run(&self, progress: &Progress) -> Result<SimulationResult, Error> {
+        assert!(self.threads > 0, "simulation needs at least one thread");
+        self.rules.validate().map_err(Error::RulesError)?;
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads)
+                .map(|worker| scope.spawn(move || self.run_worker(worker, progress)))
+                .collect();
+            let mut result = SimulationResult::default();
+            for worker in workers {
+                result.merge(&worker.join().expect("worker thread panicked")?);
+            }
+            Ok(result)
+        })
+    }
+
+    /// Plays rounds at a table of its own until the worker's share of the budget is used up.
+    fn run_worker(&self, worker: usize, progress: &Progress) -> Result<SimulationResult, Error> {
+        let budget = self.budget.share(worker, self.threads);
+        let mut shoe = Shoe::with_seed(self.decks, self.penetration, worker_seed(self.seed, worker));
+        if let Some(cards) = &self.cards {
+            shoe = shoe.with_cards(cards.iter().cloned());
+        }
+        let shoe = self.removed_ranks.iter().fold(shoe, Shoe::without_rank);
+        let shoe = shoe
+            .with_shuffle_model(self.shuffle_model)
+            .with_burn_cards(self.burn_cards);
+        if self.seats > 1 {
+            return self.run_multi_seat_worker(budget, shoe, progress);
+        }
+        let mut table = Table::new(self.chips, shoe, self.rules.clone());
+        table.fast_forward = true;
+        let mut strategy = self.strategy.clone();
+        let mut result = SimulationResult::default();
+        let mut unreported = 0;
+        while !budget.is_exhausted([&table.statistics]) {
+            if progress.is_cancelled() {
+                result.cancelled = true;
+                break;
+            }
+            if play_round(&mut table, &mut strategy)? == GameState::GameOver {
+                result.bankruptcies += 1;
+                table.chips = self.chips;
+            }
+            result.rounds_dealt += 1;
+            unreported += 1;
+            if unreported == PROGRESS_INTERVAL {
+                progress.report(unreported);
+                unreported = 0;
+            }
+        }
+        progress.report(unreported);
+        result.shuffles = table.shoe.shuffles;
+        result.seat_statistics = vec![table.statistics.clone()];
+        result.statistics = table.statistics;
+        Ok(result)
+    }
+
+    /// Plays rounds at a multi-seat table of its own until the worker's share of the budget is
+    /// used up by all seats together.
+    fn run_multi_seat_worker(
+        &self,
+        budget: Budget,
+        shoe: Shoe,
+        progress: &Progress,
+    ) -> Result<SimulationResult, Error> {
+        let seats = vec![Seat::new(self.chips, self.strategy.clone()); self.seats];
+        let mut table = MultiSeatTable::new(shoe, self.rules.clone(), seats);
+        let mut result = SimulationResult::default();
+        let mut unreported = 0;
+        while !budget.is_exhausted(table.seats.iter().map(|seat| &seat.statistics)) {
+            if progress.is_cancelled() {
+                result.cancelled = true;
+                break;
+            }
+            for seat in table.play_round()? {
+                result.bankruptcies += 1;
+                table.seats[seat].chips = self.chips;
+            }
+            result.rounds_dealt += 1;
+            // Every seat counts towards progress, just like towards the budget
+            unreported += self.seats;
+            if unreported >= PROGRESS_INTERVAL {
+                progress.report(unreported);
+                unreported = 0;
+            }
+        }
+        progress.report(unreported);
+        result.shuffles = table.shoe().shuffles;
+        for seat in table.seats {
+            result.statistics.merge(&seat.statistics);
+            result.seat_statistics.push(seat.statistics);
+        }
+        Ok(result)
+    }
+}
+
+/// Returns the seed of the shoe used by the given worker.
+/// If no seed is given, a random seed is returned.
+pub(crate) fn worker_seed(seed: Option<u64>, worker: usize) -> u64 {
+    seed.map_or_else(random, |seed| {
+        seed ^ (worker as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+    })
+}
+
+/// Plays a single round at a fast-forwarding table, starting from the betting state.
+/// Returns the state after the round, which is either betting or game over.
+/// # Errors
+/// Returns an error if the strategy gave an input which the game could not accept.
+pub fn play_round(table: &mut Table, strategy: &mut impl Strategy) -> Result<GameState, Error> {
+    let mut state = GameState::Betting;
+    loop {
+        let input = strategy::input(strategy, table, &state);
+        state = table.progress(state, input).map_err(|(_, error)| error)?;
+        if matches!(state, GameState::Betting | GameState::GameOver) {
+            return Ok(state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_simulation_is_reproducible() {
+        let simulation = Simulation {
+            budget: Budget::Rounds(5_000),
+            threads: 3,
+            seed: Some(7),
+            ..Simulation::default()
+        };
+        let first = simulation.run(&Progress::new()).unwrap();
+        let second = simulation.run(&Progress::new()).unwrap();
+        assert_eq!(first.statistics.turns_played(), 5_000);
+        assert_eq!(first.statistics.total_bet(), second.statistics.total_bet());
+        assert_eq!(first.statistics.total_won(), second.statistics.total_won());
+    }
+
+    #[test]
+    fn test_simulation_without_bets_stops() {
+        use crate::basic_strategy::BasicStrategy;
+        use crate::betting::{FlatBet, WithBetting};
+
+        let simulation = Simulation {
+            budget: Budget::Hands(10),
+            threads: 1,
+            seed: Some(3),
+            ..Simulation::default()
+        }
+        .with_strategy(WithBetting::new(BasicStrategy, FlatBet(0)));
+        let result = simulation.run(&Progress::new()).unwrap();
+        assert_eq!(result.statistics.turns_played(), 0);
+        assert_eq!(result.statistics.rounds_sat_out(), MAX_ROUNDS_SAT_OUT_IN_A_ROW);
+        assert!(!result.cancelled);
+    }
+
+    #[test]
+    fn test_cancelled_simulation_stops() {
+        let progress = Progress::new();
+        progress.cancel();
+        let result = Simulation::default().run(&progress).unwrap();
+        assert!(result.cancelled);
+        assert_eq!(result.statistics.turns_played(), 0);
+    }
+
+    #[test]
+    fn test_zero_payout_denominator_is_rejected() {
+        use crate::rules::{BlackjackPayout, RulesError};
+
+        assert_eq!(BlackjackPayout::custom(3, 0), Err(RulesError::ZeroPayoutDenominator));
+        let rules = Rules {
+            blackjack_payout: BlackjackPayout::Custom(3, 0),
+            ..Rules::default()
+        };
+        let simulation = Simulation { rules, ..Simulation::default() };
+        assert_eq!(
+            simulation.run(&Progress::new()).err(),
+            Some(Error::RulesError(RulesError::ZeroPayoutDenominator))
+        );
+    }
+}
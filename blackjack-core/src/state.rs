@@ -1,37 +1,53 @@
 use crate::card::hand::{DealerHand, PlayerHand, PlayerTurn};
+use crate::game::Wager;
 
 #[derive(Debug, Default, PartialEq, Eq)]
 pub enum GameState {
-    /// The player is placing a bet.
+    /// The player is placing a bet on one or more spots.
     #[default]
     Betting,
-    /// The dealer is dealing the first card to the player.
-    DealFirstPlayerCard { bet: u32 },
+    /// The dealer is dealing the first card to each of the player's spots.
+    DealFirstPlayerCard { wagers: Vec<Wager> },
     /// The dealer is dealing the first card to themselves.
-    DealFirstDealerCard { player_hand: PlayerHand },
-    /// The dealer is dealing the second card to the player.
+    DealFirstDealerCard { player_hands: Vec<PlayerHand> },
+    /// The dealer is dealing the second card to each of the player's spots.
     DealSecondPlayerCard {
-        player_hand: PlayerHand,
+        player_hands: Vec<PlayerHand>,
         dealer_hand: DealerHand,
     },
     /// The dealer deals the hole card to themselves.
     DealHoleCard {
-        player_hand: PlayerHand,
+        player_hands: Vec<PlayerHand>,
         dealer_hand: DealerHand,
     },
-    /// The player has a chance to surrender early (before the dealer checks for blackjack).
+    /// The player may switch the second cards of their two hands (Blackjack Switch).
+    OfferSwitch {
+        player_hands: Vec<PlayerHand>,
+        dealer_hand: DealerHand,
+    },
+    /// The player has a chance to surrender the current spot early
+    /// (before the dealer checks for blackjack).
     OfferEarlySurrender {
-        player_hand: PlayerHand,
+        player_turn: PlayerTurn,
         dealer_hand: DealerHand,
+        insurance_bet: u32,
     },
-    /// The dealer offers the player insurance because they have an ace showing.
+    /// The dealer offers the player insurance on the current spot because they have an ace showing.
     OfferInsurance {
-        player_hand: PlayerHand,
+        player_turn: PlayerTurn,
         dealer_hand: DealerHand,
+        insurance_bet: u32,
+    },
+    /// The dealer offers the player even money for the blackjack on the current spot
+    /// because they have an ace showing.
+    OfferEvenMoney {
+        player_turn: PlayerTurn,
+        dealer_hand: DealerHand,
+        insurance_bet: u32,
     },
     /// The dealer checks their hole card to see if they have blackjack.
     CheckDealerHoleCard {
-        player_hand: PlayerHand,
+        player_turn: PlayerTurn,
         dealer_hand: DealerHand,
         insurance_bet: u32,
     },
@@ -66,16 +82,17 @@ pub enum GameState {
         insurance_bet: u32,
     },
     /// The dealer is dealing the first card to the newly split hand.
+    /// The new hand is boxed to keep the game state small.
     DealFirstSplitCard {
         player_turn: PlayerTurn,
-        new_hand: PlayerHand,
+        new_hand: Box<PlayerHand>,
         dealer_hand: DealerHand,
         insurance_bet: u32,
     },
     /// The dealer has dealt the first card to the split hand, and is now dealing the second card.
     DealSecondSplitCard {
         player_turn: PlayerTurn,
-        new_hand: PlayerHand,
+        new_hand: Box<PlayerHand>,
         dealer_hand: DealerHand,
         insurance_bet: u32,
     },
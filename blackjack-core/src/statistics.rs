@@ -1,10 +1,14 @@
 use std::cmp::Ordering;
 use std::fmt::Display;
-use crate::card::hand::{DealerHand, PlayerHand, Status};
+use crate::card::hand::{Bonus, DealerHand, PlayerHand, Status};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Statistics {
     turns_played: usize,
+    /// Rounds in which the player sat out without betting
+    rounds_sat_out: usize,
+    /// Rounds the player sat out since they last played a round
+    rounds_sat_out_in_a_row: usize,
     hands_played: usize,
     total_bet: usize,
     total_won: usize,
@@ -12,9 +16,102 @@ pub struct Statistics {
     pushes: usize,
     losses: usize,
     blackjacks: usize,
+    /// Split hands of 21 with two cards, which are not counted as blackjacks
+    split_21s: usize,
     busts: usize,
+    /// Hands which won automatically by reaching the number of cards of a Charlie
+    charlies: usize,
+    /// Bonuses paid on 21 with five or more cards
+    many_card_21_bonuses: usize,
+    /// Bonuses paid on 21 made of a six, a seven and an eight
+    six_seven_eight_bonuses: usize,
+    /// Bonuses paid on 21 made of three sevens
+    seven_seven_seven_bonuses: usize,
+    /// Bonuses paid on blackjacks of a single suit
+    suited_blackjack_bonuses: usize,
     dealer_blackjacks: usize,
     dealer_busts: usize,
+    /// The sum of the bets placed at the start of each round, excluding doubles and splits
+    total_initial_bet: usize,
+    /// The net result of each round in chips, including insurance
+    round_results: Moments,
+    /// The net result of each hand in chips, excluding insurance
+    hand_results: Moments,
+    /// The results of each side bet offered at the table, which are not included above
+    side_bets: Vec<SideBetStatistics>,
+}
+
+/// The results of a side bet, which are kept apart from the results of the main game.
+#[derive(Debug, Default, Clone)]
+pub struct SideBetStatistics {
+    name: &'static str,
+    bets_placed: usize,
+    total_bet: usize,
+    total_won: usize,
+    wins: usize,
+    /// The net result of each bet in chips
+    results: Moments,
+}
+
+impl SideBetStatistics {
+    /// The name of the side bet.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The number of times the side bet was placed.
+    #[must_use]
+    pub const fn bets_placed(&self) -> usize {
+        self.bets_placed
+    }
+
+    /// The total amount of chips bet on the side bet.
+    #[must_use]
+    pub const fn total_bet(&self) -> usize {
+        self.total_bet
+    }
+
+    /// The total amount of chips paid out on the side bet, including the bets which were returned.
+    #[must_use]
+    pub const fn total_won(&self) -> usize {
+        self.total_won
+    }
+
+    /// The number of times the side bet paid out.
+    #[must_use]
+    pub const fn wins(&self) -> usize {
+        self.wins
+    }
+
+    /// The house edge as a fraction of the amount bet. Negative if the player is winning.
+    #[must_use]
+    pub fn house_edge(&self) -> f64 {
+        if self.total_bet == 0 {
+            0.0
+        } else {
+            -self.results.mean() * self.bets_placed as f64 / self.total_bet as f64
+        }
+    }
+
+    /// The standard error of the house edge.
+    #[must_use]
+    pub fn house_edge_standard_error(&self) -> f64 {
+        if self.total_bet == 0 {
+            0.0
+        } else {
+            self.results.standard_error() * self.bets_placed as f64 / self.total_bet as f64
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.name = other.name;
+        self.bets_placed += other.bets_placed;
+        self.total_bet = self.total_bet.saturating_add(other.total_bet);
+        self.total_won = self.total_won.saturating_add(other.total_won);
+        self.wins += other.wins;
+        self.results.merge(&other.results);
+    }
 }
 
 impl Statistics {
@@ -22,6 +119,8 @@ impl Statistics {
     pub const fn new() -> Self {
         Self {
             turns_played: 0,
+            rounds_sat_out: 0,
+            rounds_sat_out_in_a_row: 0,
             hands_played: 0,
             total_bet: 0,
             total_won: 0,
@@ -29,36 +128,367 @@ impl Statistics {
             pushes: 0,
             losses: 0,
             blackjacks: 0,
+            split_21s: 0,
             busts: 0,
+            charlies: 0,
+            many_card_21_bonuses: 0,
+            six_seven_eight_bonuses: 0,
+            seven_seven_seven_bonuses: 0,
+            suited_blackjack_bonuses: 0,
             dealer_blackjacks: 0,
             dealer_busts: 0,
+            total_initial_bet: 0,
+            round_results: Moments::new(),
+            hand_results: Moments::new(),
+            side_bets: Vec::new(),
         }
     }
 
     /// Update the statistics with the results of a round of blackjack.
-    pub fn update(&mut self, player_hands: Vec<PlayerHand>, dealer_hand: DealerHand) {
+    pub fn update(
+        &mut self,
+        player_hands: Vec<PlayerHand>,
+        dealer_hand: DealerHand,
+        insurance_bet: u32,
+        insurance_winnings: u32,
+    ) {
         self.turns_played += 1;
+        self.rounds_sat_out_in_a_row = 0;
         self.hands_played += player_hands.len();
+        // Split hands share the bet of the hand they were split from, so the first hand of each
+        // spot tells us what was bet on it at the start of the round
+        let mut previous_spot = None;
+        for hand in &player_hands {
+            if previous_spot != Some(hand.spot) {
+                self.total_initial_bet = self.total_initial_bet.saturating_add(hand.initial_bet() as usize);
+            }
+            previous_spot = Some(hand.spot);
+        }
+        let mut round_result = f64::from(insurance_winnings) - f64::from(insurance_bet);
         for hand in &player_hands {
             match hand.status {
+                _ if hand.is_split_21() => self.split_21s += 1,
                 Status::Blackjack => self.blackjacks += 1,
                 Status::Bust => self.busts += 1,
+                Status::Charlie => self.charlies += 1,
                 _ => {},
             }
+            match hand.paid_bonus {
+                Some(Bonus::ManyCard21(_)) => self.many_card_21_bonuses += 1,
+                Some(Bonus::SixSevenEight(_)) => self.six_seven_eight_bonuses += 1,
+                Some(Bonus::SevenSevenSeven(_)) => self.seven_seven_seven_bonuses += 1,
+                Some(Bonus::SuitedBlackjack(_)) => self.suited_blackjack_bonuses += 1,
+                None => {},
+            }
             match hand.winnings.cmp(&hand.bet) {
                 Ordering::Greater => self.wins += 1,
                 Ordering::Equal => self.pushes += 1,
                 Ordering::Less => self.losses += 1,
             }
-            self.total_bet = self.total_bet.saturating_add(hand.bet as usize);
-            self.total_won = self.total_won.saturating_add(hand.winnings as usize);
+            // Free bets put up by the house are neither bet nor won by the player
+            self.total_bet = self.total_bet.saturating_add(hand.paid_bet() as usize);
+            self.total_won = self.total_won.saturating_add(hand.payout() as usize);
+            let hand_result = f64::from(hand.payout()) - f64::from(hand.paid_bet());
+            self.hand_results.push(hand_result);
+            round_result += hand_result;
         }
+        self.round_results.push(round_result);
         match dealer_hand.status {
             Status::Blackjack => self.dealer_blackjacks += 1,
             Status::Bust => self.dealer_busts += 1,
             _ => {},
         }
     }
+
+    /// Update the statistics with a round in which the player sat out.
+    pub fn sit_out(&mut self) {
+        self.rounds_sat_out += 1;
+        self.rounds_sat_out_in_a_row += 1;
+    }
+
+    /// Update the statistics with the result of a side bet, given by its index in the table rules.
+    pub fn update_side_bet(&mut self, index: usize, name: &'static str, bet: u32, winnings: u32) {
+        if self.side_bets.len() <= index {
+            self.side_bets.resize_with(index + 1, SideBetStatistics::default);
+        }
+        let side_bet = &mut self.side_bets[index];
+        side_bet.name = name;
+        side_bet.bets_placed += 1;
+        side_bet.total_bet = side_bet.total_bet.saturating_add(bet as usize);
+        side_bet.total_won = side_bet.total_won.saturating_add(winnings as usize);
+        if winnings > 0 {
+            side_bet.wins += 1;
+        }
+        side_bet.results.push(f64::from(winnings) - f64::from(bet));
+    }
+
+    /// Merge the statistics gathered at another table into these statistics.
+    pub fn merge(&mut self, other: &Self) {
+        self.turns_played += other.turns_played;
+        self.rounds_sat_out += other.rounds_sat_out;
+        self.rounds_sat_out_in_a_row = self.rounds_sat_out_in_a_row.max(other.rounds_sat_out_in_a_row);
+        self.hands_played += other.hands_played;
+        self.total_bet = self.total_bet.saturating_add(other.total_bet);
+        self.total_won = self.total_won.saturating_add(other.total_won);
+        self.wins += other.wins;
+        self.pushes += other.pushes;
+        self.losses += other.losses;
+        self.blackjacks += other.blackjacks;
+        self.split_21s += other.split_21s;
+        self.busts += other.busts;
+        self.charlies += other.charlies;
+        self.many_card_21_bonuses += other.many_card_21_bonuses;
+        self.six_seven_eight_bonuses += other.six_seven_eight_bonuses;
+        self.seven_seven_seven_bonuses += other.seven_seven_seven_bonuses;
+        self.suited_blackjack_bonuses += other.suited_blackjack_bonuses;
+        self.dealer_blackjacks += other.dealer_blackjacks;
+        self.dealer_busts += other.dealer_busts;
+        self.total_initial_bet = self.total_initial_bet.saturating_add(other.total_initial_bet);
+        self.round_results.merge(&other.round_results);
+        self.hand_results.merge(&other.hand_results);
+        if self.side_bets.len() < other.side_bets.len() {
+            self.side_bets.resize_with(other.side_bets.len(), SideBetStatistics::default);
+        }
+        for (side_bet, other) in self.side_bets.iter_mut().zip(&other.side_bets) {
+            side_bet.merge(other);
+        }
+    }
+
+    /// The number of rounds played.
+    #[must_use]
+    pub const fn turns_played(&self) -> usize {
+        self.turns_played
+    }
+
+    /// The number of rounds in which the player sat out, which are not counted as played.
+    #[must_use]
+    pub const fn rounds_sat_out(&self) -> usize {
+        self.rounds_sat_out
+    }
+
+    /// The number of rounds the player has sat out since they last played a round.
+    #[must_use]
+    pub const fn rounds_sat_out_in_a_row(&self) -> usize {
+        self.rounds_sat_out_in_a_row
+    }
+
+    /// The number of hands played, including split hands.
+    #[must_use]
+    pub const fn hands_played(&self) -> usize {
+        self.hands_played
+    }
+
+    /// The number of blackjacks, excluding split hands of 21.
+    #[must_use]
+    pub const fn blackjacks(&self) -> usize {
+        self.blackjacks
+    }
+
+    /// The number of split hands of 21 with two cards, whether or not they count as blackjacks.
+    #[must_use]
+    pub const fn split_21s(&self) -> usize {
+        self.split_21s
+    }
+
+    /// The number of hands which won automatically by reaching the number of cards of a Charlie.
+    #[must_use]
+    pub const fn charlies(&self) -> usize {
+        self.charlies
+    }
+
+    /// The number of bonuses paid on 21 with five or more cards.
+    #[must_use]
+    pub const fn many_card_21_bonuses(&self) -> usize {
+        self.many_card_21_bonuses
+    }
+
+    /// The number of bonuses paid on 21 made of a six, a seven and an eight.
+    #[must_use]
+    pub const fn six_seven_eight_bonuses(&self) -> usize {
+        self.six_seven_eight_bonuses
+    }
+
+    /// The number of bonuses paid on 21 made of three sevens.
+    #[must_use]
+    pub const fn seven_seven_seven_bonuses(&self) -> usize {
+        self.seven_seven_seven_bonuses
+    }
+
+    /// The number of bonuses paid on blackjacks of a single suit.
+    #[must_use]
+    pub const fn suited_blackjack_bonuses(&self) -> usize {
+        self.suited_blackjack_bonuses
+    }
+
+    /// The number of rounds in which the dealer busted.
+    #[must_use]
+    pub const fn dealer_busts(&self) -> usize {
+        self.dealer_busts
+    }
+
+    /// The results of each side bet offered at the table, in the order of the rules.
+    /// Side bets which were never placed may be missing at the end.
+    #[must_use]
+    pub fn side_bets(&self) -> &[SideBetStatistics] {
+        &self.side_bets
+    }
+
+    /// The total amount of chips bet.
+    #[must_use]
+    pub const fn total_bet(&self) -> usize {
+        self.total_bet
+    }
+
+    /// The total amount of chips paid out.
+    #[must_use]
+    pub const fn total_won(&self) -> usize {
+        self.total_won
+    }
+
+    /// The net result of each round in chips, including insurance.
+    #[must_use]
+    pub const fn round_results(&self) -> &Moments {
+        &self.round_results
+    }
+
+    /// The net result of each hand in chips, excluding insurance.
+    #[must_use]
+    pub const fn hand_results(&self) -> &Moments {
+        &self.hand_results
+    }
+
+    /// The average bet placed at the start of a round, excluding doubles and splits.
+    #[must_use]
+    pub fn average_initial_bet(&self) -> f64 {
+        if self.turns_played == 0 {
+            0.0
+        } else {
+            self.total_initial_bet as f64 / self.turns_played as f64
+        }
+    }
+
+    /// The house edge as a fraction of the initial bet, i.e. the average loss per chip
+    /// bet at the start of a round. Negative if the player is winning.
+    #[must_use]
+    pub fn house_edge(&self) -> f64 {
+        let average_initial_bet = self.average_initial_bet();
+        if average_initial_bet == 0.0 {
+            0.0
+        } else {
+            -self.round_results.mean() / average_initial_bet
+        }
+    }
+
+    /// The standard error of the house edge.
+    #[must_use]
+    pub fn house_edge_standard_error(&self) -> f64 {
+        let average_initial_bet = self.average_initial_bet();
+        if average_initial_bet == 0.0 {
+            0.0
+        } else {
+            self.round_results.standard_error() / average_initial_bet
+        }
+    }
+
+    /// The 95% confidence interval of the house edge, as (lower, upper) bounds.
+    #[must_use]
+    pub fn house_edge_confidence_interval_95(&self) -> (f64, f64) {
+        let margin = Z_95 * self.house_edge_standard_error();
+        (self.house_edge() - margin, self.house_edge() + margin)
+    }
+}
+
+/// The z-score of a two-sided 95% confidence interval under the normal distribution.
+const Z_95: f64 = 1.96;
+
+/// The running mean and variance of a sequence of samples, computed with Welford's algorithm.
+/// This is numerically stable even for millions of samples, and two sets of moments
+/// can be merged without access to the original samples.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Moments {
+    count: usize,
+    mean: f64,
+    m2: f64,
+}
+
+impl Moments {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+
+    /// Add a sample.
+    pub fn push(&mut self, sample: f64) {
+        self.count += 1;
+        let delta = sample - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (sample - self.mean);
+    }
+
+    /// Merge the moments of another sequence of samples into these moments.
+    pub fn merge(&mut self, other: &Self) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * (self.count as f64 * other.count as f64) / count as f64;
+        self.count = count;
+    }
+
+    /// The number of samples.
+    #[must_use]
+    pub const fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The mean of the samples, or 0 if there are none.
+    #[must_use]
+    pub const fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// The sample variance, or 0 if there are fewer than two samples.
+    #[must_use]
+    pub fn variance(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            self.m2 / (self.count - 1) as f64
+        }
+    }
+
+    /// The sample standard deviation.
+    #[must_use]
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// The standard error of the mean.
+    #[must_use]
+    pub fn standard_error(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.std_dev() / (self.count as f64).sqrt()
+        }
+    }
+
+    /// The 95% confidence interval of the mean, as (lower, upper) bounds.
+    #[must_use]
+    pub fn confidence_interval_95(&self) -> (f64, f64) {
+        let margin = Z_95 * self.standard_error();
+        (self.mean - margin, self.mean + margin)
+    }
 }
 
 impl Display for Statistics {
@@ -79,6 +509,9 @@ impl Display for Statistics {
         }
 
         writeln!(f, "Turns Played: {}", self.turns_played)?;
+        if self.rounds_sat_out > 0 {
+            writeln!(f, "Rounds Sat Out: {}", self.rounds_sat_out)?;
+        }
         writeln!(f, "Hands Played: {}", self.hands_played)?;
         writeln!(f, "Total Bet: {} Chips", self.total_bet)?;
         writeln!(f, "Average Bet: {} Chips", div(self.total_bet, self.hands_played))?;
@@ -88,10 +521,121 @@ impl Display for Statistics {
         writeln!(f, "Pushes: {} ({}%)", self.pushes, pct(self.pushes, self.hands_played))?;
         writeln!(f, "Losses: {} ({}%)", self.losses, pct(self.losses, self.hands_played))?;
         writeln!(f, "Blackjacks: {} ({}%)", self.blackjacks, pct(self.blackjacks, self.hands_played))?;
+        writeln!(f, "Split 21s: {} ({}%)", self.split_21s, pct(self.split_21s, self.hands_played))?;
         writeln!(f, "Busts: {} ({}%)", self.busts, pct(self.busts, self.hands_played))?;
+        if self.charlies > 0 {
+            writeln!(f, "Charlies: {} ({}%)", self.charlies, pct(self.charlies, self.hands_played))?;
+        }
+        let bonuses = [
+            ("Many-Card 21", self.many_card_21_bonuses),
+            ("6-7-8", self.six_seven_eight_bonuses),
+            ("7-7-7", self.seven_seven_seven_bonuses),
+            ("Suited Blackjack", self.suited_blackjack_bonuses),
+        ];
+        for (name, count) in bonuses.into_iter().filter(|&(_, count)| count > 0) {
+            writeln!(f, "{name} Bonuses: {count} ({}%)", pct(count, self.hands_played))?;
+        }
         writeln!(f, "Dealer Blackjacks: {} ({}%)", self.dealer_blackjacks, pct(self.dealer_blackjacks, self.hands_played))?;
         writeln!(f, "Dealer Busts: {} ({}%)", self.dealer_busts, pct(self.dealer_busts, self.hands_played))?;
+        let (low, high) = self.house_edge_confidence_interval_95();
+        writeln!(f, "House Edge: {:.3}%", self.house_edge() * 100.0)?;
+        writeln!(f, "  95% CI: {:.3}% to {:.3}%", low * 100.0, high * 100.0)?;
+        writeln!(f, "  Std. Error: {:.3}%", self.house_edge_standard_error() * 100.0)?;
+        writeln!(f, "Std. Dev. per Round: {:.2} Chips", self.round_results.std_dev())?;
+        writeln!(f, "Std. Dev. per Hand: {:.2} Chips", self.hand_results.std_dev())?;
+        for side_bet in self.side_bets.iter().filter(|side_bet| side_bet.bets_placed > 0) {
+            writeln!(
+                f,
+                "{}: {} Bets, {} Wins ({}%), House Edge {:.3}%",
+                side_bet.name,
+                side_bet.bets_placed,
+                side_bet.wins,
+                pct(side_bet.wins, side_bet.bets_placed),
+                side_bet.house_edge() * 100.0
+            )?;
+        }
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::shoe::Shoe;
+    use crate::card::{Rank, Suit};
+    use crate::game::{HandAction, Input, Table};
+    use crate::rules::{Rules, SuitedBlackjackBonus};
+    use crate::state::GameState;
+    use crate::test_helpers::{card, dealer, hand_of, suited};
+
+    #[test]
+    fn test_merged_moments_match_sequential_moments() {
+        let samples = [-100.0, 150.0, 0.0, -200.0, 100.0, 100.0, -50.0];
+        let mut sequential = Moments::new();
+        samples.iter().for_each(|&sample| sequential.push(sample));
+        let (left, right) = samples.split_at(3);
+        let mut merged = Moments::new();
+        left.iter().for_each(|&sample| merged.push(sample));
+        let mut other = Moments::new();
+        right.iter().for_each(|&sample| other.push(sample));
+        merged.merge(&other);
+        assert_eq!(merged.count(), sequential.count());
+        assert!((merged.mean() - sequential.mean()).abs() < 1e-9);
+        assert!((merged.variance() - sequential.variance()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_split_21s_are_not_blackjacks() {
+        let mut hand = hand_of([Rank::Ace, Rank::Ace].map(card), 100);
+        let mut new_hand = hand.split();
+        hand += card(Rank::King);
+        new_hand += card(Rank::Ten);
+        assert_eq!(hand.status, Status::Stood);
+        let mut statistics = Statistics::new();
+        statistics.update(vec![hand, new_hand], dealer([Rank::Nine, Rank::Nine].map(card)), 0, 0);
+        assert_eq!(statistics.blackjacks(), 0);
+        assert_eq!(statistics.split_21s(), 2);
+    }
+
+    #[test]
+    fn test_charlies_and_bonuses_are_counted() {
+        let rules = Rules {
+            charlie: Some(5),
+            suited_blackjack_bonus: Some(SuitedBlackjackBonus { suited: (2, 1), spades: (3, 1) }),
+            ..Rules::default()
+        };
+        // The player hits 2 and 2 to a five-card Charlie against the dealer's 19,
+        // then is dealt a blackjack in spades against the dealer's 16
+        let cards = [
+            (Rank::Two, Suit::Hearts),
+            (Rank::King, Suit::Clubs),
+            (Rank::Two, Suit::Hearts),
+            (Rank::Nine, Suit::Clubs),
+            (Rank::Two, Suit::Hearts),
+            (Rank::Three, Suit::Hearts),
+            (Rank::Five, Suit::Hearts),
+            (Rank::Ace, Suit::Spades),
+            (Rank::Nine, Suit::Clubs),
+            (Rank::King, Suit::Spades),
+            (Rank::Seven, Suit::Clubs),
+        ];
+        let shoe = Shoe::stacked(cards.map(|(rank, suit)| suited(rank, suit)).into());
+        let mut table = Table::new(1_000, shoe, rules);
+        for _ in 0..2 {
+            let mut state = table.progress(GameState::Betting, Some(Input::Bets(vec![100]))).unwrap();
+            while state != GameState::Betting {
+                let input = matches!(state, GameState::PlayPlayerTurn { .. })
+                    .then_some(Input::Action(HandAction::Hit));
+                state = table.progress(state, input).unwrap();
+            }
+        }
+        // The Charlie pays 1:1 and the blackjack in spades 3:1
+        assert_eq!(table.chips, 1_000 - 100 + 200 - 100 + 400);
+        let mut merged = Statistics::new();
+        merged.merge(&table.statistics);
+        merged.merge(&table.statistics);
+        assert_eq!((merged.charlies(), merged.suited_blackjack_bonuses()), (2, 2));
+        assert_eq!((merged.many_card_21_bonuses(), merged.six_seven_eight_bonuses()), (0, 0));
+    }
+}
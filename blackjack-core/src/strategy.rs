@@ -0,0 +1,283 @@
+//! The decisions a player makes during a game of blackjack.
+//! A strategy only gets a read-only view of the table and the cards which are visible to the player:
+//! their own hands and the dealer's up card.
+
+use crate::card::hand::{DealerHand, PlayerHand, PlayerTurn};
+use crate::card::shoe::ShoeView;
+use crate::card::Card;
+use crate::game::{DoubleError, HandAction, Input, SplitError, SurrenderError, Table, Wager};
+use crate::rules::Rules;
+use crate::state::GameState;
+use crate::statistics::Statistics;
+
+/// What the player can see of the table: the rules, their own chips and results,
+/// and the cards which have been seen from the shoe, but not the cards left in it.
+#[derive(Debug, Clone, Copy)]
+pub struct TableView<'a> {
+    table: &'a Table,
+}
+
+impl<'a> TableView<'a> {
+    #[must_use]
+    pub const fn new(table: &'a Table) -> Self {
+        Self { table }
+    }
+
+    /// The table rules
+    #[must_use]
+    pub const fn rules(&self) -> &'a Rules {
+        &self.table.rules
+    }
+
+    /// The player's chips at this table
+    #[must_use]
+    pub const fn chips(&self) -> u32 {
+        self.table.chips
+    }
+
+    /// The player's results so far
+    #[must_use]
+    pub const fn statistics(&self) -> &'a Statistics {
+        &self.table.statistics
+    }
+
+    /// The cards which have been seen from the shoe
+    #[must_use]
+    pub const fn shoe(&self) -> ShoeView<'a> {
+        self.table.shoe.view()
+    }
+
+    /// See [`Table::check_hit_allowed`].
+    /// # Errors
+    ///
+    /// Returns why the player may not hit their current hand
+    pub fn check_hit_allowed(&self, player_turn: &PlayerTurn) -> Result<(), SplitError> {
+        self.table.check_hit_allowed(player_turn)
+    }
+
+    /// See [`Table::check_double_allowed`].
+    /// # Errors
+    ///
+    /// Returns why the player may not double down on their current hand
+    pub fn check_double_allowed(&self, player_turn: &PlayerTurn) -> Result<(), DoubleError> {
+        self.table.check_double_allowed(player_turn)
+    }
+
+    /// See [`Table::check_split_allowed`].
+    /// # Errors
+    ///
+    /// Returns why the player may not split their current hand
+    pub fn check_split_allowed(&self, player_turn: &PlayerTurn) -> Result<(), SplitError> {
+        self.table.check_split_allowed(player_turn)
+    }
+
+    /// See [`Table::check_surrender_allowed`].
+    /// # Errors
+    ///
+    /// Returns why the player may not surrender the hand
+    pub fn check_surrender_allowed(&self, hand: &PlayerHand) -> Result<(), SurrenderError> {
+        self.table.check_surrender_allowed(hand)
+    }
+}
+
+/// What the player can see of the dealer's hand before the dealer's turn: only the up card.
+#[derive(Debug, Clone, Copy)]
+pub struct DealerView<'a> {
+    hand: &'a DealerHand,
+}
+
+impl<'a> DealerView<'a> {
+    #[must_use]
+    pub const fn new(hand: &'a DealerHand) -> Self {
+        Self { hand }
+    }
+
+    /// Returns the worth of the dealer's up card.
+    #[must_use]
+    pub fn showing(&self) -> u8 {
+        self.hand.showing()
+    }
+
+    /// Returns the dealer's up card.
+    #[must_use]
+    pub fn up_card(&self) -> &'a Card {
+        self.hand.up_card()
+    }
+
+    /// Returns whether the dealer hits on soft 17.
+    #[must_use]
+    pub fn hits_on_soft_17(&self) -> bool {
+        self.hand.hits_on_soft_17()
+    }
+}
+
+/// A player's strategy.
+/// Strategies may keep state between decisions, e.g. to track previous results.
+pub trait Strategy {
+    /// Decide how much to bet at the start of a round, or 0 to sit out the round.
+    fn bet(&mut self, table: TableView) -> u32;
+
+    /// Decide how much to bet on each spot at the start of a round, e.g. to play several spots
+    /// when the count is high. A bet of 0 leaves the spot empty, and the player sits out the round
+    /// if every spot is left empty.
+    /// By default, the player only plays a single spot, or two if they may switch cards.
+    fn bet_spots(&mut self, table: TableView) -> Vec<u32> {
+        vec![self.bet(table); if table.rules().switch { 2 } else { 1 }]
+    }
+
+    /// Decide how much to bet on each side bet offered at the table, in the order of the rules.
+    /// The same side bets are placed on every spot the player bets on.
+    /// By default, the player places no side bets.
+    fn side_bets(&mut self, _table: TableView) -> Vec<u32> {
+        Vec::new()
+    }
+
+    /// Decide whether to surrender a spot before the dealer checks for blackjack.
+    fn surrender_early(
+        &mut self,
+        table: TableView,
+        player_hand: &PlayerHand,
+        dealer_hand: DealerView,
+    ) -> bool;
+
+    /// Decide how much to bet on insurance for a spot, at most half of its original bet.
+    fn bet_insurance(
+        &mut self,
+        table: TableView,
+        player_hand: &PlayerHand,
+        dealer_hand: DealerView,
+    ) -> u32;
+
+    /// Decide whether to take even money for a blackjack against the dealer's ace.
+    /// Even money is the same as insuring a blackjack, so it is declined by default.
+    fn take_even_money(
+        &mut self,
+        _table: TableView,
+        _player_hand: &PlayerHand,
+        _dealer_hand: DealerView,
+    ) -> bool {
+        false
+    }
+
+    /// Decide whether to switch the second cards of the player's two hands, as in Blackjack Switch.
+    /// By default, the player keeps their cards.
+    fn switch_cards(
+        &mut self,
+        _table: TableView,
+        _player_hands: &[PlayerHand],
+        _dealer_hand: DealerView,
+    ) -> bool {
+        false
+    }
+
+    /// Decide how to play the current hand of the player's turn.
+    fn play_hand(
+        &mut self,
+        table: TableView,
+        player_turn: &PlayerTurn,
+        dealer_hand: DealerView,
+    ) -> HandAction;
+
+}
+
+/// Returns the input the given strategy gives in the given state,
+/// or None if the state does not require any input.
+/// The strategy only gets to see what the player can see of the table and the dealer's hand.
+pub fn input<S: Strategy + ?Sized>(strategy: &mut S, table: &Table, state: &GameState) -> Option<Input> {
+    let table = TableView::new(table);
+    match state {
+        GameState::Betting => {
+            let bets = strategy.bet_spots(table);
+            if bets.iter().all(|&bet| bet == 0) {
+                return Some(Input::SitOut);
+            }
+            let side_bets = strategy.side_bets(table);
+            if side_bets.is_empty() {
+                Some(Input::Bets(bets))
+            } else {
+                let wagers = bets
+                    .into_iter()
+                    .map(|bet| Wager { bet, side_bets: side_bets.clone() })
+                    .collect();
+                Some(Input::Wagers(wagers))
+            }
+        }
+        GameState::OfferSwitch { player_hands, dealer_hand } => Some(Input::Choice(
+            strategy.switch_cards(table, player_hands, DealerView::new(dealer_hand)),
+        )),
+        GameState::OfferEarlySurrender { player_turn, dealer_hand, .. } => Some(Input::Choice(
+            strategy.surrender_early(table, player_turn.current_hand(), DealerView::new(dealer_hand)),
+        )),
+        GameState::OfferInsurance { player_turn, dealer_hand, .. } => Some(Input::Bet(
+            strategy.bet_insurance(table, player_turn.current_hand(), DealerView::new(dealer_hand)),
+        )),
+        GameState::OfferEvenMoney { player_turn, dealer_hand, .. } => Some(Input::Choice(
+            strategy.take_even_money(table, player_turn.current_hand(), DealerView::new(dealer_hand)),
+        )),
+        GameState::PlayPlayerTurn { player_turn, dealer_hand, .. } => Some(Input::Action(
+            strategy.play_hand(table, player_turn, DealerView::new(dealer_hand)),
+        )),
+        _ => None,
+    }
+}
+
+/// A boxed strategy plays like the strategy inside it,
+/// so players with different strategies can share a table.
+impl<S: Strategy + ?Sized> Strategy for Box<S> {
+    fn bet(&mut self, table: TableView) -> u32 {
+        (**self).bet(table)
+    }
+
+    fn bet_spots(&mut self, table: TableView) -> Vec<u32> {
+        (**self).bet_spots(table)
+    }
+
+    fn side_bets(&mut self, table: TableView) -> Vec<u32> {
+        (**self).side_bets(table)
+    }
+
+    fn surrender_early(
+        &mut self,
+        table: TableView,
+        player_hand: &PlayerHand,
+        dealer_hand: DealerView,
+    ) -> bool {
+        (**self).surrender_early(table, player_hand, dealer_hand)
+    }
+
+    fn bet_insurance(
+        &mut self,
+        table: TableView,
+        player_hand: &PlayerHand,
+        dealer_hand: DealerView,
+    ) -> u32 {
+        (**self).bet_insurance(table, player_hand, dealer_hand)
+    }
+
+    fn take_even_money(
+        &mut self,
+        table: TableView,
+        player_hand: &PlayerHand,
+        dealer_hand: DealerView,
+    ) -> bool {
+        (**self).take_even_money(table, player_hand, dealer_hand)
+    }
+
+    fn switch_cards(
+        &mut self,
+        table: TableView,
+        player_hands: &[PlayerHand],
+        dealer_hand: DealerView,
+    ) -> bool {
+        (**self).switch_cards(table, player_hands, dealer_hand)
+    }
+
+    fn play_hand(
+        &mut self,
+        table: TableView,
+        player_turn: &PlayerTurn,
+        dealer_hand: DealerView,
+    ) -> HandAction {
+        (**self).play_hand(table, player_turn, dealer_hand)
+    }
+}
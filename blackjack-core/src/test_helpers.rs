@@ -0,0 +1,37 @@
+//! Helpers for building the cards and hands used in tests.
+
+use crate::card::hand::{DealerHand, PlayerHand};
+use crate::card::{Card, Rank, Suit};
+use crate::rules::DealerSoft17Action;
+
+/// A card of the given rank, for tests where the suit doesn't matter.
+pub const fn card(rank: Rank) -> Card {
+    suited(rank, Suit::Hearts)
+}
+
+/// A card of the given rank and suit.
+pub const fn suited(rank: Rank, suit: Suit) -> Card {
+    Card { rank, suit }
+}
+
+/// A player's hand of the given cards, in the order they are dealt.
+/// # Panics
+///
+/// Panics if there are no cards
+pub fn hand_of(cards: impl IntoIterator<Item = Card>, bet: u32) -> PlayerHand {
+    let mut cards = cards.into_iter();
+    let mut hand = PlayerHand::new(cards.next().expect("a hand needs a card"), bet);
+    cards.for_each(|card| hand += card);
+    hand
+}
+
+/// A dealer's hand of the given cards, starting with the up card. The dealer stands on soft 17.
+/// # Panics
+///
+/// Panics if there are no cards
+pub fn dealer(cards: impl IntoIterator<Item = Card>) -> DealerHand {
+    let mut cards = cards.into_iter();
+    let mut hand = DealerHand::new(cards.next().expect("a hand needs a card"), DealerSoft17Action::Stand);
+    cards.for_each(|card| hand += card);
+    hand
+}
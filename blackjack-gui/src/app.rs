@@ -41,6 +41,7 @@ impl App {
             KeyCode::Esc => self.should_quit = true,
             KeyCode::Char('g') => self.add_game(),
             KeyCode::Char('q') => self.delete_game(),
+            KeyCode::Char('c') => self.next_count_system(),
             KeyCode::Up => self.cursor_up(),
             KeyCode::Down => self.cursor_down(),
             key => self.input_current_game(key),
@@ -61,6 +62,12 @@ impl App {
         }
     }
     
+    pub fn next_count_system(&mut self) {
+        if let Some(game) = self.games.get_mut(self.selected_game) {
+            game.next_count_system();
+        }
+    }
+    
     pub fn cursor_up(&mut self) {
         self.selected_game = (self.selected_game + self.games.len() - 1) % self.games.len();
     }
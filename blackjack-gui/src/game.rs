@@ -1,9 +1,11 @@
 use crossterm::event::KeyCode;
-use blackjack_core::basic_strategy;
+use blackjack_core::basic_strategy::BasicStrategy;
 use blackjack_core::game::{Input, Table, Error};
 use blackjack_core::card::shoe::Shoe;
+use blackjack_core::counting::CountSystem;
 use blackjack_core::rules::Rules;
 use blackjack_core::state::GameState;
+use blackjack_core::strategy;
 use crate::input::InputField;
 
 #[derive(Debug)]
@@ -12,6 +14,8 @@ pub struct Blackjack {
     pub game_state: GameState,
     pub input_field: Option<InputField>,
     pub last_error: Option<Error>,
+    pub strategy: BasicStrategy,
+    pub count_system: CountSystem,
 }
 
 impl Blackjack {
@@ -19,7 +23,7 @@ impl Blackjack {
         let table = Table::new(50000, Shoe::new(4, 0.50), Rules::default());
         let game_state = GameState::Betting;
         let input_field = InputField::from_game(&game_state, &table);
-        Self { table, game_state, input_field, last_error: None }
+        Self { table, game_state, input_field, last_error: None, strategy: BasicStrategy, count_system: CountSystem::default() }
     }
     
     pub fn tick(&mut self) {
@@ -41,8 +45,15 @@ impl Blackjack {
         }
     }
     
+    /// Switches to the next built-in count system.
+    pub fn next_count_system(&mut self) {
+        let systems = CountSystem::ALL;
+        let current = systems.iter().position(|system| *system == self.count_system).unwrap_or(0);
+        self.count_system = systems[(current + 1) % systems.len()];
+    }
+
     pub fn simulate(&mut self) {
-        let input = self.basic_strategy_input();
+        let input = strategy::input(&mut self.strategy, &self.table, &self.game_state);
         if let Err(transition_error) = self.try_progress(input) {
             self.last_error = Some(transition_error);
         } else {
@@ -64,18 +75,4 @@ impl Blackjack {
             },
         }
     }
-
-    pub fn basic_strategy_input(&self) -> Option<Input> {
-        match &self.game_state {
-            GameState::Betting => Some(Input::Bet(basic_strategy::bet())),
-            GameState::OfferEarlySurrender { player_hand, dealer_hand } => Some(Input::Choice(
-                basic_strategy::surrender_early(&self.table, player_hand, dealer_hand),
-            )),
-            GameState::OfferInsurance { .. } => Some(Input::Bet(basic_strategy::bet_insurance())),
-            GameState::PlayPlayerTurn { player_turn, dealer_hand, .. } => Some(Input::Action(
-                basic_strategy::play_hand(&self.table, player_turn, dealer_hand),
-            )),
-            _ => None,
-        }
-    }
 }
\ No newline at end of file
@@ -8,6 +8,8 @@ pub enum InputField {
     PlaceBet(String),
     PlaceInsuranceBet(String),
     ChooseSurrender,
+    ChooseEvenMoney,
+    ChooseSwitch,
     PlayHand(Vec<HandAction>),
 }
 
@@ -17,9 +19,13 @@ impl InputField {
             GameState::Betting => Some(Self::PlaceBet(String::new())),
             GameState::OfferInsurance { .. } => Some(Self::PlaceInsuranceBet(String::new())),
             GameState::OfferEarlySurrender { .. } => Some(Self::ChooseSurrender),
+            GameState::OfferEvenMoney { .. } => Some(Self::ChooseEvenMoney),
+            GameState::OfferSwitch { .. } => Some(Self::ChooseSwitch),
             GameState::PlayPlayerTurn { player_turn, .. } => {
                 let mut allowed_actions = Vec::with_capacity(5);
-                allowed_actions.push(HandAction::Hit);
+                if table.check_hit_allowed(player_turn).is_ok() {
+                    allowed_actions.push(HandAction::Hit);
+                }
                 allowed_actions.push(HandAction::Stand);
                 if table.check_double_allowed(player_turn).is_ok() {
                     allowed_actions.push(HandAction::Double);
@@ -27,7 +33,7 @@ impl InputField {
                 if table.check_split_allowed(player_turn).is_ok() {
                     allowed_actions.push(HandAction::Split);
                 }
-                if table.check_surrender_allowed(&player_turn.current_hand()).is_ok() {
+                if table.check_surrender_allowed(player_turn.current_hand()).is_ok() {
                     allowed_actions.push(HandAction::Surrender);
                 }
                 Some(Self::PlayHand(allowed_actions))
@@ -38,14 +44,28 @@ impl InputField {
     
     pub fn consider(&mut self, key_code: KeyCode) -> Option<Input> {
         match self {
-            Self::PlaceBet(s) => parse_bet_from_string(key_code, s),
+            Self::PlaceBet(s) => parse_bets_from_string(key_code, s),
             Self::PlaceInsuranceBet(s) => parse_bet_from_string(key_code, s),
-            Self::ChooseSurrender => select_choice(key_code),
+            Self::ChooseSurrender | Self::ChooseEvenMoney | Self::ChooseSwitch => select_choice(key_code),
             Self::PlayHand(_) => select_action(key_code),
         }
     }
 }
 
+/// Bets on several spots are separated by commas, e.g. "100,200".
+/// Betting nothing on every spot sits out the round.
+fn parse_bets_from_string(key: KeyCode, field: &mut String) -> Option<Input> {
+    if key == KeyCode::Enter {
+        let bets: Result<Vec<u32>, _> = field.split(',').map(|bet| bet.trim().parse()).collect();
+        match bets {
+            Ok(bets) if bets.iter().all(|&bet| bet == 0) => return Some(Input::SitOut),
+            Ok(bets) if bets.len() > 1 => return Some(Input::Bets(bets)),
+            _ => {}
+        }
+    }
+    parse_bet_from_string(key, field)
+}
+
 fn parse_bet_from_string(key: KeyCode, field: &mut String) -> Option<Input> {
     if key == KeyCode::Enter {
         if let Ok(bet) = field.parse() {
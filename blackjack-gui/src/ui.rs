@@ -4,7 +4,8 @@ use ratatui::prelude::*;
 use ratatui::widgets::block::Title;
 use ratatui::widgets::{Block, Borders, Paragraph};
 
-use blackjack_core::card::hand::Status;
+use blackjack_core::card::hand::{PlayerHand, Status};
+use blackjack_core::game::Wager;
 use blackjack_core::state::GameState;
 
 use crate::app::App;
@@ -38,7 +39,16 @@ fn draw_games_list(frame: &mut Frame, app: &App, area: Rect) {
 fn draw_statistics_section(frame: &mut Frame, app: &App, area: Rect) {
     let block = Block::default().title("Statistics").borders(Borders::ALL);
     if let Some(current_game) = app.current_game() {
-        let content = Paragraph::new(format!("{}", current_game.table.statistics)).block(block);
+        let shoe = current_game.table.shoe.view();
+        let system = &current_game.count_system;
+        let content = Paragraph::new(format!(
+            "{}\nCount ({system}, (c) to switch)\nRunning Count: {:.1}\nTrue Count: {:.1}\nDecks Remaining: {:.1}",
+            current_game.table.statistics,
+            system.running_count(shoe),
+            system.true_count(shoe),
+            shoe.decks_remaining(),
+        ))
+        .block(block);
         frame.render_widget(content, area);
     } else {
         frame.render_widget(block, area);
@@ -59,11 +69,13 @@ fn draw_input_area(frame: &mut Frame, app: &App, area: Rect) {
                 .input_field
                 .as_ref()
                 .map_or_else(String::new, |input_field| match input_field {
-                    InputField::PlaceBet(s) => format!("Enter your bet: {s}"),
+                    InputField::PlaceBet(s) => format!("Enter your bet, or bets separated by commas (0 to sit out): {s}"),
                     InputField::PlaceInsuranceBet(s) => {
                         format!("Place an insurance bet? Enter bet or 0: {s}")
                     }
                     InputField::ChooseSurrender => "Surrender? (y) or (n)".to_string(),
+                    InputField::ChooseEvenMoney => "Take even money? (y) or (n)".to_string(),
+                    InputField::ChooseSwitch => "Switch second cards? (y) or (n)".to_string(),
                     InputField::PlayHand(actions) => {
                         let mut output = String::with_capacity(actions.len() * 7);
                         for action in actions {
@@ -104,44 +116,58 @@ fn draw_game(frame: &mut Frame, app: &App, area: Rect) {
 fn game_text(game_state: &GameState) -> String {
     match game_state {
         GameState::Betting => "Place your bet!".to_string(),
-        GameState::DealFirstPlayerCard { bet } => {
-            format!("DealFirstPlayerCard\nBet: {bet}\n")
+        GameState::DealFirstPlayerCard { wagers } => {
+            format!("DealFirstPlayerCard\nBet: {}\n", bet_values(wagers))
         }
-        GameState::DealFirstDealerCard { player_hand } => {
-            format!("DealFirstDealerCard\nPlayer: {}\n", player_hand.value)
+        GameState::DealFirstDealerCard { player_hands } => {
+            format!("DealFirstDealerCard\nPlayer: {}\n", hand_values(player_hands))
         }
-        GameState::DealSecondPlayerCard { player_hand, dealer_hand } => {
+        GameState::DealSecondPlayerCard { player_hands, dealer_hand } => {
             format!(
                 "DealSecondPlayerCard\nPlayer: {}\nDealer showing: {}\n",
-                player_hand.value,
+                hand_values(player_hands),
                 dealer_hand.showing()
             )
         }
-        GameState::DealHoleCard { player_hand, dealer_hand } => {
+        GameState::DealHoleCard { player_hands, dealer_hand } => {
             format!(
                 "DealHoleCard\nPlayer: {}\nDealer showing: {}\n",
-                player_hand.value,
+                hand_values(player_hands),
                 dealer_hand.showing()
             )
         }
-        GameState::OfferEarlySurrender { player_hand, dealer_hand } => {
+        GameState::OfferSwitch { player_hands, dealer_hand } => {
+            format!(
+                "OfferSwitch\nPlayer: {}\nDealer showing: {}\n",
+                hand_cards(player_hands),
+                dealer_hand.showing()
+            )
+        }
+        GameState::OfferEarlySurrender { player_turn, dealer_hand, .. } => {
             format!(
                 "OfferEarlySurrender\nPlayer: {}\nDealer showing: {}\n",
-                player_hand.value,
+                player_turn.current_hand().value,
                 dealer_hand.showing()
             )
         }
-        GameState::OfferInsurance { player_hand, dealer_hand } => {
+        GameState::OfferInsurance { player_turn, dealer_hand, .. } => {
             format!(
                 "OfferInsurance\nPlayer: {}\nDealer showing: {}\n",
-                player_hand.value,
+                player_turn.current_hand().value,
+                dealer_hand.showing(),
+            )
+        }
+        GameState::OfferEvenMoney { player_turn, dealer_hand, .. } => {
+            format!(
+                "OfferEvenMoney\nPlayer: {}\nDealer showing: {}\n",
+                player_turn.current_hand().value,
                 dealer_hand.showing(),
             )
         }
-        GameState::CheckDealerHoleCard { player_hand, dealer_hand, insurance_bet: insurance } => {
+        GameState::CheckDealerHoleCard { player_turn, dealer_hand, insurance_bet: insurance } => {
             format!(
                 "CheckDealerHoleCard\nPlayer: {}\nDealer showing: {}\n{}\n",
-                player_hand.value,
+                player_turn.current_hand().value,
                 dealer_hand.showing(),
                 if *insurance > 0 {
                     format!("Insurance: {insurance}")
@@ -272,3 +298,22 @@ fn game_text(game_state: &GameState) -> String {
         GameState::GameOver => "Game Over!".to_string(),
     }
 }
+
+/// Lists the values of the hands on each of the player's spots.
+fn hand_values(hands: &[PlayerHand]) -> String {
+    hands.iter().map(|hand| hand.value.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// Lists the cards of each of the player's hands, e.g. to decide whether to switch them.
+fn hand_cards(hands: &[PlayerHand]) -> String {
+    hands
+        .iter()
+        .map(|hand| hand.cards.iter().map(ToString::to_string).collect::<Vec<_>>().join(" and "))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Lists the bets on each of the player's spots, including side bets.
+fn bet_values(wagers: &[Wager]) -> String {
+    wagers.iter().map(|wager| wager.total().to_string()).collect::<Vec<_>>().join(", ")
+}